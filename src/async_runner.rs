@@ -0,0 +1,275 @@
+use crate::filter::By;
+use crate::query::Queryable;
+use crate::runner::{WaitError, WaitOptions, query_condition, removal_condition};
+use crate::{NodeT, State, TreeNode};
+use std::future::Future;
+use std::iter::FusedIterator;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use web_time::Instant;
+
+/// Advances an application that is driven by async tasks.
+///
+/// This is the async version of [`crate::Runner`]. Use it if your app needs the executor to make
+/// progress between frames, where a blocking wait loop would deadlock.
+///
+/// The async helpers in kittest don't depend on a specific executor. They only yield back to the
+/// executor between frames, so any executor (or a minimal `block_on`) can drive them.
+pub trait AsyncRunner {
+    /// Run a single frame of the application and update the [`State`] with the new
+    /// `TreeUpdate`.
+    fn step(&mut self) -> impl Future<Output = ()>;
+
+    /// The current kittest [`State`].
+    fn state(&self) -> &State;
+
+    /// The limits used by the async wait helpers.
+    fn wait_options(&self) -> WaitOptions {
+        WaitOptions::default()
+    }
+
+    /// Wait for the [`WaitOptions::step_interval`] after each frame.
+    ///
+    /// kittest doesn't know your executor's timer, so by default this only yields to the
+    /// executor once and ignores the duration. Override it to respect the interval, e.g. with
+    /// `tokio::time::sleep(duration)`.
+    fn sleep(&self, _duration: Duration) -> impl Future<Output = ()> {
+        yield_now()
+    }
+}
+
+/// A future that returns `Pending` once, to give other tasks on the executor a chance to run.
+struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            Poll::Ready(())
+        } else {
+            self.yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Yield back to the executor once.
+pub async fn yield_now() {
+    YieldNow { yielded: false }.await;
+}
+
+/// Step the runner until `condition` returns `Ok`, using the runner's
+/// [`AsyncRunner::wait_options`].
+///
+/// This is the async version of [`crate::wait_for`].
+///
+/// # Errors
+/// Returns the last error of the condition if the limits are hit before it returns `Ok`.
+pub async fn wait_for_async<R: AsyncRunner + ?Sized, T, E>(
+    runner: &mut R,
+    condition: impl FnMut(TreeNode<'_>) -> Result<T, E>,
+) -> Result<T, WaitError<E>> {
+    let options = runner.wait_options();
+    wait_for_async_with_options(runner, options, condition).await
+}
+
+/// Step the runner until `condition` returns `Ok`, using the given [`WaitOptions`].
+///
+/// After each frame, the runner waits for the [`WaitOptions::step_interval`] with
+/// [`AsyncRunner::sleep`].
+///
+/// # Errors
+/// Returns the last error of the condition if the limits are hit before it returns `Ok`.
+pub async fn wait_for_async_with_options<R: AsyncRunner + ?Sized, T, E>(
+    runner: &mut R,
+    options: WaitOptions,
+    mut condition: impl FnMut(TreeNode<'_>) -> Result<T, E>,
+) -> Result<T, WaitError<E>> {
+    let start = Instant::now();
+    let mut frames = 0;
+
+    loop {
+        let last_error = match condition(TreeNode::new(runner.state().root())) {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+
+        let elapsed = start.elapsed();
+        if frames >= options.max_frames || elapsed >= options.timeout {
+            return Err(WaitError {
+                frames,
+                elapsed,
+                last_error,
+            });
+        }
+
+        runner.step().await;
+        frames += 1;
+
+        runner.sleep(options.step_interval).await;
+    }
+}
+
+/// Step the runner until no node matches `by`.
+///
+/// This is the async version of [`crate::wait_for_removal`].
+///
+/// # Errors
+/// Returns an error describing the remaining node if the limits are hit before it is removed.
+#[allow(clippy::needless_pass_by_value)]
+pub async fn wait_for_removal_async<R: AsyncRunner + ?Sized>(
+    runner: &mut R,
+    by: By<'_>,
+) -> Result<(), WaitError<String>> {
+    wait_for_async(runner, removal_condition(&by)).await
+}
+
+/// Step the runner until a node in the tree matches the given [`By`] filter.
+///
+/// This is the async version of [`crate::Findable::find`].
+///
+/// # Panics
+/// - if no nodes are found within the [`AsyncRunner::wait_options`] limits.
+/// - if more than one node is found matching the query.
+pub async fn find_async<'tree, 'node, R, Node>(runner: &'node mut R, by: By<'tree>) -> Node
+where
+    R: AsyncRunner + Queryable<'tree, 'node, Node> + ?Sized,
+    Node: NodeT<'tree> + 'tree,
+{
+    if let Err(err) = wait_for_async(runner, query_condition(&by)).await {
+        panic!("{err}");
+    }
+    let runner: &'node R = runner;
+    runner.get(by)
+}
+
+/// Step the runner until at least one node in the tree matches the given [`By`] filter.
+///
+/// This is the async version of [`crate::Findable::find_all`].
+///
+/// # Panics
+/// - if no nodes are found within the [`AsyncRunner::wait_options`] limits.
+pub async fn find_all_async<'tree, 'node, R, Node>(
+    runner: &'node mut R,
+    by: By<'tree>,
+) -> impl DoubleEndedIterator<Item = Node> + FusedIterator<Item = Node> + 'tree
+where
+    R: AsyncRunner + Queryable<'tree, 'node, Node> + ?Sized,
+    Node: NodeT<'tree> + 'tree,
+{
+    if let Err(err) = wait_for_async(runner, query_condition(&by)).await {
+        panic!("{err}");
+    }
+    let runner: &'node R = runner;
+    runner.get_all(by)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::buttons;
+    use std::cell::RefCell;
+    use std::pin::pin;
+    use std::task::Waker;
+
+    /// A minimal executor: poll the future until it is ready.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Shows a "Done" button after `frames_until_done` frames.
+    struct TestRunner {
+        state: State,
+        frame: usize,
+        frames_until_done: usize,
+        options: WaitOptions,
+        sleeps: RefCell<Vec<Duration>>,
+    }
+
+    impl TestRunner {
+        fn new(frames_until_done: usize) -> Self {
+            Self {
+                state: State::new(buttons(&["Loading"])),
+                frame: 0,
+                frames_until_done,
+                options: WaitOptions::new().max_frames(10),
+                sleeps: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl AsyncRunner for TestRunner {
+        async fn step(&mut self) {
+            yield_now().await;
+            self.frame += 1;
+            let labels: &[&str] = if self.frame >= self.frames_until_done {
+                &["Done"]
+            } else {
+                &["Loading"]
+            };
+            self.state.update(buttons(labels));
+        }
+
+        fn state(&self) -> &State {
+            &self.state
+        }
+
+        fn wait_options(&self) -> WaitOptions {
+            self.options
+        }
+
+        async fn sleep(&self, duration: Duration) {
+            self.sleeps.borrow_mut().push(duration);
+        }
+    }
+
+    #[test]
+    fn wait_for_async_steps_until_found() {
+        let mut runner = TestRunner::new(3);
+        block_on(wait_for_removal_async(
+            &mut runner,
+            By::new().label("Loading"),
+        ))
+        .unwrap();
+        assert_eq!(runner.frame, 3);
+        assert!(runner.state.query_by_label("Done").is_some());
+    }
+
+    #[test]
+    fn wait_for_async_gives_up_after_max_frames() {
+        let mut runner = TestRunner::new(100);
+        let err = block_on(wait_for_removal_async(
+            &mut runner,
+            By::new().label("Loading"),
+        ))
+        .unwrap_err();
+        assert_eq!(err.frames, 10);
+        assert_eq!(runner.frame, 10);
+    }
+
+    #[test]
+    fn wait_for_async_sleeps_with_the_runner() {
+        let mut runner = TestRunner::new(2);
+        runner.options = runner.options.step_interval(Duration::from_millis(20));
+        block_on(wait_for_removal_async(
+            &mut runner,
+            By::new().label("Loading"),
+        ))
+        .unwrap();
+        assert_eq!(
+            runner.sleeps.into_inner(),
+            vec![Duration::from_millis(20); 2]
+        );
+    }
+}
@@ -0,0 +1,617 @@
+use crate::changes::NodeSummary;
+use crate::contrast::ColorContrast;
+use crate::geometry::{OverlappingTargets, TargetSize};
+use crate::outline::{ContentOutsideLandmarks, HeadingOrder, SingleMain};
+use crate::rules::{
+    DuplicateLabelledBy, EmptyHeading, FocusableHidden, ImageDescription, MissingName,
+};
+use crate::tab_order::KeyboardFocusable;
+use crate::{AccessKitNode, State};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+
+/// How bad a [`Violation`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Worth a look, but not necessarily a problem.
+    Info,
+
+    /// Likely a problem for some users.
+    Warning,
+
+    /// Makes the UI inaccessible for some users.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Info => write!(f, "info"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// The conformance level of a [`WcagCriterion`].
+#[allow(clippy::upper_case_acronyms)] // That's how WCAG spells them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WcagLevel {
+    /// The minimum level, which all content should meet.
+    A,
+
+    /// The level most accessibility laws and policies require.
+    AA,
+
+    /// The highest level, which isn't achievable for all content.
+    AAA,
+}
+
+/// A WCAG 2.2 success criterion, see <https://www.w3.org/TR/WCAG22/>.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WcagCriterion {
+    /// The number, e.g. `"4.1.2"`.
+    pub id: &'static str,
+    /// The title, e.g. `"Name, Role, Value"`.
+    pub name: &'static str,
+    /// The conformance level the criterion belongs to.
+    pub level: WcagLevel,
+}
+
+impl WcagCriterion {
+    /// Images and other non-text content need a text alternative.
+    pub const NON_TEXT_CONTENT: Self = Self::new("1.1.1", "Non-text Content", WcagLevel::A);
+    /// Structure, like headings and landmarks, needs to be exposed to assistive technology.
+    pub const INFO_AND_RELATIONSHIPS: Self =
+        Self::new("1.3.1", "Info and Relationships", WcagLevel::A);
+    /// Text needs a contrast ratio of at least 4.5:1, or 3:1 for large text.
+    pub const CONTRAST_MINIMUM: Self = Self::new("1.4.3", "Contrast (Minimum)", WcagLevel::AA);
+    /// Text needs a contrast ratio of at least 7:1, or 4.5:1 for large text.
+    pub const CONTRAST_ENHANCED: Self = Self::new("1.4.6", "Contrast (Enhanced)", WcagLevel::AAA);
+    /// Everything needs to be usable with the keyboard.
+    pub const KEYBOARD: Self = Self::new("2.1.1", "Keyboard", WcagLevel::A);
+    /// The focus needs to move in a meaningful order.
+    pub const FOCUS_ORDER: Self = Self::new("2.4.3", "Focus Order", WcagLevel::A);
+    /// Headings and labels need to describe their topic or purpose.
+    pub const HEADINGS_AND_LABELS: Self = Self::new("2.4.6", "Headings and Labels", WcagLevel::AA);
+    /// Pointer targets need to be at least 24x24, or spaced apart.
+    pub const TARGET_SIZE_MINIMUM: Self =
+        Self::new("2.5.8", "Target Size (Minimum)", WcagLevel::AA);
+    /// Interactive nodes need a name, role and value that assistive technology can read.
+    pub const NAME_ROLE_VALUE: Self = Self::new("4.1.2", "Name, Role, Value", WcagLevel::A);
+
+    /// A criterion that isn't one of the constants above.
+    pub const fn new(id: &'static str, name: &'static str, level: WcagLevel) -> Self {
+        Self { id, name, level }
+    }
+}
+
+impl Display for WcagCriterion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WCAG {} {} ({:?})", self.id, self.name, self.level)
+    }
+}
+
+/// An accessibility problem found by a [`Rule`], see [`audit`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// The node with the problem.
+    pub node: NodeSummary,
+    /// The [`Rule::id`] of the rule that found the problem.
+    pub rule_id: &'static str,
+    pub severity: Severity,
+    /// The success criteria the rule checks, see [`Rule::wcag`].
+    pub wcag: &'static [WcagCriterion],
+    pub message: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} [{}] {}: {}",
+            self.severity, self.rule_id, self.node, self.message
+        )
+    }
+}
+
+/// An accessibility check, run for every node of the tree by [`audit`] and [`audit_node`].
+///
+/// Most rules only look at a single node and implement [`Rule::check`]. Rules that compare
+/// nodes with each other implement [`Rule::check_tree`] instead, so they can collect what they
+/// need in a single pass over the tree.
+///
+/// ```rust ignore
+/// struct NoUnknownRole;
+///
+/// impl Rule for NoUnknownRole {
+///     fn id(&self) -> &'static str {
+///         "no-unknown-role"
+///     }
+///
+///     fn check(&self, node: &AccessKitNode<'_>) -> Option<String> {
+///         (node.role() == Role::Unknown).then(|| "Node has no role".to_owned())
+///     }
+/// }
+///
+/// let mut rules = default_rules();
+/// rules.push(Box::new(NoUnknownRole));
+/// let violations = audit_node(&harness.state().root(), &rules);
+/// ```
+pub trait Rule {
+    /// A unique, kebab-case id, e.g. `"missing-name"`.
+    fn id(&self) -> &'static str;
+
+    /// The severity of violations of this rule.
+    /// Default is [`Severity::Error`].
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    /// The WCAG success criteria this rule checks.
+    /// Default is none.
+    fn wcag(&self) -> &'static [WcagCriterion] {
+        &[]
+    }
+
+    /// Check a single node. Return a message describing the problem, or `None` if the node
+    /// is fine.
+    /// Default is `None`, for rules that implement [`Self::check_tree`] instead.
+    fn check(&self, _node: &AccessKitNode<'_>) -> Option<String> {
+        None
+    }
+
+    /// Check `root` and all its descendants. Return the nodes with problems, each with a
+    /// message describing the problem.
+    /// Default calls [`Self::check`] for every node.
+    fn check_tree<'tree>(
+        &self,
+        root: &AccessKitNode<'tree>,
+    ) -> Vec<(AccessKitNode<'tree>, String)> {
+        let mut problems = Vec::new();
+        visit(root, &mut |node| {
+            if let Some(message) = self.check(node) {
+                problems.push((*node, message));
+            }
+        });
+        problems
+    }
+}
+
+/// Call `f` for `node` and all its descendants, in tree order.
+pub(crate) fn visit<'tree>(node: &AccessKitNode<'tree>, f: &mut impl FnMut(&AccessKitNode<'tree>)) {
+    f(node);
+    for child in node.children() {
+        visit(&child, f);
+    }
+}
+
+/// The built-in rules:
+/// - `missing-name`: interactive nodes need an accessible name
+/// - `image-description`: images need a label or description
+/// - `duplicate-labelled-by`: a label should only label a single node
+/// - `focusable-hidden`: hidden nodes shouldn't be focusable
+/// - `empty-heading`: headings need text
+/// - `color-contrast`: text needs enough contrast, see [`ColorContrast`]
+/// - `target-size`: interactive nodes need to be at least 24x24 or spaced apart, see [`TargetSize`]
+/// - `overlapping-targets`: interactive nodes shouldn't overlap
+/// - `keyboard-focusable`: clickable nodes need to be focusable
+/// - `heading-order`: heading levels shouldn't be skipped
+/// - `single-main`: there should only be one main landmark
+/// - `content-outside-landmarks`: if there are landmarks, all text should be in one
+pub fn default_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(MissingName),
+        Box::new(ImageDescription),
+        Box::new(DuplicateLabelledBy),
+        Box::new(FocusableHidden),
+        Box::new(EmptyHeading),
+        Box::new(ColorContrast::new()),
+        Box::new(TargetSize::new()),
+        Box::new(OverlappingTargets),
+        Box::new(KeyboardFocusable),
+        Box::new(HeadingOrder),
+        Box::new(SingleMain),
+        Box::new(ContentOutsideLandmarks),
+    ]
+}
+
+/// Check the whole tree with the global [`AuditConfig`], see [`AuditConfig::global`].
+/// Use [`AuditConfig::audit`] to audit with a different config.
+///
+/// ```rust ignore
+/// let violations = audit(harness.state());
+/// assert!(violations.is_empty(), "{violations:#?}");
+/// ```
+pub fn audit(state: &State) -> Vec<Violation> {
+    AuditConfig::global().audit(&state.root())
+}
+
+/// Check `root` and all its descendants with the given rules.
+/// Violations are returned in tree order.
+pub fn audit_node(root: &AccessKitNode<'_>, rules: &[Box<dyn Rule>]) -> Vec<Violation> {
+    let rules: Vec<&dyn Rule> = rules.iter().map(AsRef::as_ref).collect();
+    run_rules(root, &rules)
+}
+
+/// Run the rules on the tree, returning the violations in tree order (and in rule order for the
+/// same node).
+fn run_rules(root: &AccessKitNode<'_>, rules: &[&dyn Rule]) -> Vec<Violation> {
+    let mut positions = HashMap::new();
+    visit(root, &mut |node| {
+        positions.insert(node.id(), positions.len());
+    });
+
+    let mut violations = Vec::new();
+    for (rule_index, rule) in rules.iter().enumerate() {
+        for (node, message) in rule.check_tree(root) {
+            let position = positions.get(&node.id()).copied().unwrap_or(usize::MAX);
+            violations.push((
+                (position, rule_index),
+                Violation {
+                    node: NodeSummary::new(&node),
+                    rule_id: rule.id(),
+                    severity: rule.severity(),
+                    wcag: rule.wcag(),
+                    message,
+                },
+            ));
+        }
+    }
+    violations.sort_by_key(|(order, _)| *order);
+    violations
+        .into_iter()
+        .map(|(_, violation)| violation)
+        .collect()
+}
+
+/// Which rules [`AuditConfig::audit`] and [`assert_no_a11y_violations`] run, with which
+/// severity, and which violations fail the assertion.
+///
+/// ```rust ignore
+/// // For all tests, e.g. in a shared test helper:
+/// fn audit_config() -> AuditConfig {
+///     AuditConfig::new().disable("empty-heading")
+/// }
+/// AuditConfig::set_global(audit_config);
+/// assert_no_a11y_violations!(harness);
+///
+/// // For a single test:
+/// let config = audit_config().severity("missing-name", Severity::Warning);
+/// assert_no_a11y_violations!(harness, &config);
+/// ```
+pub struct AuditConfig {
+    rules: Vec<Box<dyn Rule>>,
+    disabled: Vec<String>,
+    severities: HashMap<String, Severity>,
+    failure_threshold: Severity,
+}
+
+/// See [`AuditConfig::set_global`].
+static GLOBAL_CONFIG: OnceLock<fn() -> AuditConfig> = OnceLock::new();
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for AuditConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditConfig")
+            .field(
+                "rules",
+                &self.rules.iter().map(|rule| rule.id()).collect::<Vec<_>>(),
+            )
+            .field("disabled", &self.disabled)
+            .field("severities", &self.severities)
+            .field("failure_threshold", &self.failure_threshold)
+            .finish()
+    }
+}
+
+impl AuditConfig {
+    /// Run the [`default_rules`] with their default severity, failing on errors.
+    pub fn new() -> Self {
+        Self {
+            rules: default_rules(),
+            disabled: Vec::new(),
+            severities: HashMap::new(),
+            failure_threshold: Severity::Error,
+        }
+    }
+
+    /// The config used by [`audit`] and the one-argument form of
+    /// [`crate::assert_no_a11y_violations!`]: the one set with [`Self::set_global`], or
+    /// [`Self::new`].
+    pub fn global() -> Self {
+        GLOBAL_CONFIG
+            .get()
+            .map_or_else(Self::new, |config| config())
+    }
+
+    /// Set the config returned by [`Self::global`], for all tests in the test binary.
+    ///
+    /// Only the first call has an effect, so this can be called at the start of every test,
+    /// e.g. from a shared test helper. Returns false if a global config was already set.
+    pub fn set_global(config: fn() -> Self) -> bool {
+        GLOBAL_CONFIG.set(config).is_ok()
+    }
+
+    /// Add a custom rule. Replaces the rule with the same [`Rule::id`], if there is one.
+    pub fn rule(mut self, rule: impl Rule + 'static) -> Self {
+        self.rules.retain(|existing| existing.id() != rule.id());
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Don't run the rule with the given [`Rule::id`].
+    pub fn disable(mut self, rule_id: impl Into<String>) -> Self {
+        self.disabled.push(rule_id.into());
+        self
+    }
+
+    /// Run a rule again after it was disabled with [`Self::disable`].
+    pub fn enable(mut self, rule_id: &str) -> Self {
+        self.disabled.retain(|disabled| disabled != rule_id);
+        self
+    }
+
+    /// Report violations of the rule with the given [`Rule::id`] with a different severity.
+    pub fn severity(mut self, rule_id: impl Into<String>, severity: Severity) -> Self {
+        self.severities.insert(rule_id.into(), severity);
+        self
+    }
+
+    /// Make [`assert_no_a11y_violations`] fail for violations with at least this severity.
+    /// Default is [`Severity::Error`].
+    pub fn failure_threshold(mut self, severity: Severity) -> Self {
+        self.failure_threshold = severity;
+        self
+    }
+
+    /// Returns true if the rule with the given id runs.
+    pub fn is_enabled(&self, rule_id: &str) -> bool {
+        self.rules.iter().any(|rule| rule.id() == rule_id)
+            && !self.disabled.iter().any(|disabled| disabled == rule_id)
+    }
+
+    /// Check `root` and all its descendants with the enabled rules.
+    /// Violations are returned in tree order.
+    pub fn audit(&self, root: &AccessKitNode<'_>) -> Vec<Violation> {
+        let rules: Vec<&dyn Rule> = self
+            .rules
+            .iter()
+            .filter(|rule| self.is_enabled(rule.id()))
+            .map(AsRef::as_ref)
+            .collect();
+        let mut violations = run_rules(root, &rules);
+        for violation in &mut violations {
+            if let Some(severity) = self.severities.get(violation.rule_id) {
+                violation.severity = *severity;
+            }
+        }
+        violations
+    }
+}
+
+/// Violations grouped by rule, for readable failure messages.
+///
+/// ```text
+/// 3 accessibility violations (2 errors, 1 warning)
+///
+/// error [missing-name] WCAG 4.1.2 Name, Role, Value (A)
+///   button (#4): Interactive node has no accessible name
+///   checkBox (#7): Interactive node has no accessible name
+///
+/// warning [empty-heading] WCAG 2.4.6 Headings and Labels (AA)
+///   heading (#2): Heading has no text
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    /// The violations, in the order they were found.
+    pub violations: Vec<Violation>,
+}
+
+impl AuditReport {
+    /// A report of the given violations.
+    pub fn new(violations: Vec<Violation>) -> Self {
+        Self { violations }
+    }
+
+    /// Returns true if there are no violations.
+    pub fn is_empty(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl Display for AuditReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No accessibility violations");
+        }
+
+        let count = |severity: Severity| {
+            self.violations
+                .iter()
+                .filter(|violation| violation.severity == severity)
+                .count()
+        };
+        let plural = |count: usize, word: &str| {
+            format!("{count} {word}{}", if count == 1 { "" } else { "s" })
+        };
+        let counts = [
+            (
+                count(Severity::Error),
+                plural(count(Severity::Error), "error"),
+            ),
+            (
+                count(Severity::Warning),
+                plural(count(Severity::Warning), "warning"),
+            ),
+            (
+                count(Severity::Info),
+                format!("{} info", count(Severity::Info)),
+            ),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(_, text)| text)
+        .collect::<Vec<_>>()
+        .join(", ");
+        writeln!(
+            f,
+            "{} ({counts})",
+            plural(self.violations.len(), "accessibility violation")
+        )?;
+
+        // Group by rule and severity, most severe first, in order of appearance.
+        let mut groups: Vec<(&'static str, Severity, Vec<&Violation>)> = Vec::new();
+        for violation in &self.violations {
+            match groups.iter_mut().find(|(rule_id, severity, _)| {
+                *rule_id == violation.rule_id && *severity == violation.severity
+            }) {
+                Some((_, _, group)) => group.push(violation),
+                None => groups.push((violation.rule_id, violation.severity, vec![violation])),
+            }
+        }
+        groups.sort_by_key(|(_, severity, _)| std::cmp::Reverse(*severity));
+
+        for (rule_id, severity, violations) in groups {
+            write!(f, "\n{severity} [{rule_id}]")?;
+            let wcag = violations
+                .first()
+                .map(|violation| violation.wcag)
+                .unwrap_or_default();
+            for criterion in wcag {
+                write!(f, " {criterion}")?;
+            }
+            writeln!(f)?;
+            for violation in violations {
+                writeln!(f, "  {}: {}", violation.node, violation.message)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Assert that the tree has no accessibility violations at or above the
+/// [`AuditConfig::failure_threshold`], see [`crate::assert_no_a11y_violations!`].
+///
+/// # Panics
+/// - if there are any such violations, with an [`AuditReport`] of them as message.
+#[track_caller]
+pub fn assert_no_a11y_violations(root: &AccessKitNode<'_>, config: &AuditConfig) {
+    let mut violations = config.audit(root);
+    violations.retain(|violation| violation.severity >= config.failure_threshold);
+    let report = AuditReport::new(violations);
+    assert!(report.is_empty(), "{report}");
+}
+
+/// Assert that a harness, state or node has no accessibility violations.
+///
+/// Uses the global [`AuditConfig`] (see [`AuditConfig::global`]), unless a config is passed as
+/// the second argument.
+///
+/// ```rust ignore
+/// assert_no_a11y_violations!(harness);
+/// assert_no_a11y_violations!(harness, &AuditConfig::new().disable("empty-heading"));
+/// ```
+///
+/// # Panics
+/// - if there are any violations at or above the [`AuditConfig::failure_threshold`], with a
+///   report grouped by rule.
+#[macro_export]
+macro_rules! assert_no_a11y_violations {
+    ($root:expr) => {
+        $crate::assert_no_a11y_violations!($root, &$crate::AuditConfig::global())
+    };
+    ($root:expr, $config:expr) => {
+        $crate::assert_no_a11y_violations(
+            &$crate::NodeT::accesskit_node(&$crate::Queryable::queryable_node(&$root)),
+            $config,
+        )
+    };
+}
+
+/// Returns true if the node or one of its ancestors is hidden.
+pub(crate) fn is_hidden_in_tree(node: &AccessKitNode<'_>) -> bool {
+    let mut current = Some(*node);
+    while let Some(node) = current {
+        if node.is_hidden() {
+            return true;
+        }
+        current = node.parent();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{node, state};
+    use accesskit::Role;
+
+    /// A window with a button without a name.
+    fn unnamed_button() -> State {
+        state([
+            (0, node(Role::Window, None, &[1])),
+            (1, node(Role::Button, None, &[])),
+        ])
+    }
+
+    #[test]
+    #[should_panic(expected = "1 accessibility violation (1 error)")]
+    fn errors_fail() {
+        assert_no_a11y_violations(&unnamed_button().root(), &AuditConfig::new());
+    }
+
+    #[test]
+    fn lowered_severity_passes() {
+        let state = unnamed_button();
+        let config = AuditConfig::new().severity("missing-name", Severity::Warning);
+        assert_eq!(config.audit(&state.root()).len(), 1);
+        assert_no_a11y_violations(&state.root(), &config);
+    }
+
+    #[test]
+    #[should_panic(expected = "1 accessibility violation (1 warning)")]
+    fn failure_threshold() {
+        let config = AuditConfig::new()
+            .severity("missing-name", Severity::Warning)
+            .failure_threshold(Severity::Warning);
+        assert_no_a11y_violations(&unnamed_button().root(), &config);
+    }
+
+    #[test]
+    fn global_config() {
+        // The only test setting the global config, since it is shared by all tests.
+        assert!(AuditConfig::set_global(|| {
+            AuditConfig::new().severity("missing-name", Severity::Warning)
+        }));
+        assert!(!AuditConfig::set_global(AuditConfig::new));
+        let severities: Vec<Severity> = audit(&unnamed_button())
+            .iter()
+            .map(|violation| violation.severity)
+            .collect();
+        assert_eq!(severities, [Severity::Warning]);
+        crate::assert_no_a11y_violations!(unnamed_button());
+    }
+
+    #[test]
+    fn report_counts() {
+        let state = unnamed_button();
+        let mut violations = AuditConfig::new().audit(&state.root());
+        violations.extend(
+            AuditConfig::new()
+                .severity("missing-name", Severity::Info)
+                .audit(&state.root()),
+        );
+        let report = AuditReport::new(violations).to_string();
+        assert_eq!(
+            report.lines().next(),
+            Some("2 accessibility violations (1 error, 1 info)")
+        );
+    }
+}
@@ -0,0 +1,313 @@
+use crate::AccessKitNode;
+use crate::live::AnnouncementCollector;
+use crate::node::accessible_name;
+use crate::redact::Redactions;
+use crate::snapshot::role_name;
+use crate::validate::NodeIndex;
+use accesskit::{NodeId, Role};
+use std::fmt::{Display, Formatter};
+
+/// Identifies a node in [`FrameChanges`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeSummary {
+    /// The local AccessKit node id.
+    pub id: NodeId,
+    pub role: Role,
+    /// The label, or the value for nodes like `Role::Label`.
+    pub name: Option<String>,
+}
+
+impl NodeSummary {
+    pub(crate) fn new(node: &AccessKitNode<'_>) -> Self {
+        Self {
+            id: node.locate().0,
+            role: node.role(),
+            name: accessible_name(node),
+        }
+    }
+}
+
+impl Display for NodeSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", role_name(self.role))?;
+        if let Some(name) = &self.name {
+            write!(f, " {name:?}")?;
+        }
+        write!(f, " (#{})", self.id.0)
+    }
+}
+
+/// A changed property of a node.
+/// `None` means that the property isn't set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PropertyChange {
+    pub property: &'static str,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl Display for PropertyChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let fmt_value = |value: &Option<String>| value.as_deref().unwrap_or("<unset>").to_owned();
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.property,
+            fmt_value(&self.old),
+            fmt_value(&self.new)
+        )
+    }
+}
+
+/// A node that changed, see [`FrameChanges::updated`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdatedNode {
+    pub node: NodeSummary,
+    pub changes: Vec<PropertyChange>,
+}
+
+/// A change of the focused node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FocusMove {
+    pub from: Option<NodeSummary>,
+    pub to: Option<NodeSummary>,
+}
+
+/// An entry of [`crate::State::focus_history`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FocusChange {
+    /// The frame (see [`crate::State::frame`]) in which the focus changed.
+    pub frame: usize,
+    /// The newly focused node, or `None` if nothing is focused.
+    pub node: Option<NodeSummary>,
+}
+
+impl Display for FocusChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.node {
+            Some(node) => write!(f, "frame {}: {node}", self.frame),
+            None => write!(f, "frame {}: nothing", self.frame),
+        }
+    }
+}
+
+/// What changed in a single `TreeUpdate`, see [`crate::State::last_changes`].
+///
+/// ```rust ignore
+/// harness.get_by_label("Save").click();
+/// harness.run();
+/// let changes = harness.state().last_changes();
+/// assert_eq!(changes.updated.len(), 1, "Unexpected changes:\n{changes}");
+/// assert_eq!(changes.updated[0].node.name.as_deref(), Some("Saved"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FrameChanges {
+    pub added: Vec<NodeSummary>,
+    /// Nodes where at least one property changed.
+    /// Focus changes are only reported in [`Self::focus`].
+    pub updated: Vec<UpdatedNode>,
+    pub removed: Vec<NodeSummary>,
+    pub focus: Option<FocusMove>,
+}
+
+impl FrameChanges {
+    /// Returns true if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.updated.is_empty()
+            && self.removed.is_empty()
+            && self.focus.is_none()
+    }
+}
+
+impl Display for FrameChanges {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes");
+        }
+        for node in &self.added {
+            writeln!(f, "added: {node}")?;
+        }
+        for updated in &self.updated {
+            writeln!(f, "updated: {}", updated.node)?;
+            for change in &updated.changes {
+                writeln!(f, "  {change}")?;
+            }
+        }
+        for node in &self.removed {
+            writeln!(f, "removed: {node}")?;
+        }
+        if let Some(focus) = &self.focus {
+            let fmt_node = |node: &Option<NodeSummary>| {
+                node.as_ref()
+                    .map_or_else(|| "nothing".to_owned(), ToString::to_string)
+            };
+            writeln!(
+                f,
+                "focus: {} -> {}",
+                fmt_node(&focus.from),
+                fmt_node(&focus.to)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The properties kittest compares between frames, in a stable order.
+/// Properties that aren't set are left out.
+pub(crate) fn node_properties(node: &AccessKitNode<'_>) -> Vec<(&'static str, String)> {
+    node_properties_with(node, &Redactions::new())
+}
+
+/// Like [`node_properties`], but with redacted values and bounds.
+pub(crate) fn node_properties_with(
+    node: &AccessKitNode<'_>,
+    redactions: &Redactions<'_>,
+) -> Vec<(&'static str, String)> {
+    let data = node.data();
+    let mut properties = Vec::new();
+    let mut push = |name, value: Option<String>| {
+        if let Some(value) = value {
+            properties.push((name, value));
+        }
+    };
+
+    push("role", Some(role_name(node.role())));
+    push("label", node.label().map(|label| format!("{label:?}")));
+    push(
+        "value",
+        redactions.value(node).map(|value| format!("{value:?}")),
+    );
+    push(
+        "numeric_value",
+        redactions.numeric_value(node).map(|v| v.to_string()),
+    );
+    push(
+        "description",
+        node.description()
+            .map(|description| format!("{description:?}")),
+    );
+    push(
+        "toggled",
+        node.toggled().map(|toggled| format!("{toggled:?}")),
+    );
+    push("expanded", data.is_expanded().map(|v| v.to_string()));
+    push("selected", node.is_selected().map(|v| v.to_string()));
+    push("level", node.level().map(|v| v.to_string()));
+    push("disabled", node.is_disabled().then(|| "true".to_owned()));
+    push("hidden", node.is_hidden().then(|| "true".to_owned()));
+    push("read_only", data.is_read_only().then(|| "true".to_owned()));
+    push("required", node.is_required().then(|| "true".to_owned()));
+    push(
+        "bounds",
+        node.bounding_box().map(|r| {
+            format!(
+                "{},{} {}x{}",
+                redactions.number(r.x0),
+                redactions.number(r.y0),
+                redactions.number(r.width()),
+                redactions.number(r.height())
+            )
+        }),
+    );
+    push(
+        "children",
+        Some(format!(
+            "{:?}",
+            node.children()
+                .map(|child| child.locate().0.0)
+                .collect::<Vec<_>>()
+        )),
+    );
+
+    properties
+}
+
+/// Compare the properties of two versions of a node.
+pub(crate) fn property_changes(
+    old: &AccessKitNode<'_>,
+    new: &AccessKitNode<'_>,
+) -> Vec<PropertyChange> {
+    diff_properties(&node_properties(old), &node_properties(new))
+}
+
+/// Compare two lists of properties, as returned by [`node_properties`].
+pub(crate) fn diff_properties(
+    old: &[(&'static str, String)],
+    new: &[(&'static str, String)],
+) -> Vec<PropertyChange> {
+    let mut names: Vec<&'static str> = old.iter().map(|(name, _)| *name).collect();
+    for (name, _) in new {
+        if !names.contains(name) {
+            names.push(name);
+        }
+    }
+
+    let find = |properties: &[(&'static str, String)], name: &str| {
+        properties
+            .iter()
+            .find(|(property, _)| *property == name)
+            .map(|(_, value)| value.clone())
+    };
+    names
+        .into_iter()
+        .filter_map(|property| {
+            let old = find(old, property);
+            let new = find(new, property);
+            (old != new).then_some(PropertyChange { property, old, new })
+        })
+        .collect()
+}
+
+/// Collects the [`FrameChanges`] and live region announcements of a `TreeUpdate`, and keeps the
+/// [`NodeIndex`] up to date.
+pub(crate) struct ChangeCollector {
+    pub(crate) changes: FrameChanges,
+    pub(crate) announcements: AnnouncementCollector,
+    pub(crate) node_index: NodeIndex,
+}
+
+impl ChangeCollector {
+    pub(crate) fn new(frame: usize, node_index: NodeIndex) -> Self {
+        Self {
+            changes: FrameChanges::default(),
+            announcements: AnnouncementCollector::new(frame),
+            node_index,
+        }
+    }
+}
+
+impl accesskit_consumer::TreeChangeHandler for ChangeCollector {
+    fn node_added(&mut self, node: &AccessKitNode<'_>) {
+        self.node_index.insert(node);
+        self.changes.added.push(NodeSummary::new(node));
+        self.announcements.node_changed(node);
+    }
+
+    fn node_updated(&mut self, old_node: &AccessKitNode<'_>, new_node: &AccessKitNode<'_>) {
+        self.announcements.node_updated(old_node, new_node);
+        let changes = property_changes(old_node, new_node);
+        if !changes.is_empty() {
+            self.changes.updated.push(UpdatedNode {
+                node: NodeSummary::new(new_node),
+                changes,
+            });
+        }
+    }
+
+    fn focus_moved(
+        &mut self,
+        old_node: Option<&AccessKitNode<'_>>,
+        new_node: Option<&AccessKitNode<'_>>,
+    ) {
+        self.changes.focus = Some(FocusMove {
+            from: old_node.map(NodeSummary::new),
+            to: new_node.map(NodeSummary::new),
+        });
+    }
+
+    fn node_removed(&mut self, node: &AccessKitNode<'_>) {
+        self.node_index.remove(node);
+        self.changes.removed.push(NodeSummary::new(node));
+    }
+}
@@ -0,0 +1,286 @@
+use crate::AccessKitNode;
+use crate::audit::{Rule, WcagCriterion, WcagLevel, is_hidden_in_tree};
+use crate::live::node_text;
+use accesskit::Color;
+
+/// The relative luminance of an sRGB color, as defined by WCAG.
+/// Alpha is ignored.
+fn relative_luminance(color: Color) -> f64 {
+    let channel = |value: u8| {
+        let value = f64::from(value) / 255.0;
+        if value <= 0.04045 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * channel(color.red) + 0.7152 * channel(color.green) + 0.0722 * channel(color.blue)
+}
+
+/// The WCAG contrast ratio between two opaque colors, from 1.0 (no contrast) to 21.0
+/// (black on white).
+pub fn contrast_ratio(a: Color, b: Color) -> f64 {
+    let a = relative_luminance(a);
+    let b = relative_luminance(b);
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// Draw `top` over the opaque color `bottom`.
+fn blend(top: Color, bottom: Color) -> Color {
+    let alpha = f64::from(top.alpha) / 255.0;
+    let mix = |top: u8, bottom: u8| {
+        // The result is in 0..=255, so the cast can't truncate.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let mixed = (f64::from(top) * alpha + f64::from(bottom) * (1.0 - alpha)).round() as u8;
+        mixed
+    };
+    Color {
+        red: mix(top.red, bottom.red),
+        green: mix(top.green, bottom.green),
+        blue: mix(top.blue, bottom.blue),
+        alpha: 255,
+    }
+}
+
+/// The foreground color of the node or its closest ancestor that sets one.
+fn effective_foreground(node: &AccessKitNode<'_>) -> Option<Color> {
+    let mut current = Some(*node);
+    while let Some(node) = current {
+        if let Some(color) = node.data().foreground_color() {
+            return Some(color);
+        }
+        current = node.parent();
+    }
+    None
+}
+
+/// The background behind the node: its own and its ancestors' backgrounds, blended until an
+/// opaque one is reached. `None` if there is no opaque background.
+fn effective_background(node: &AccessKitNode<'_>) -> Option<Color> {
+    let mut layers = Vec::new();
+    let mut current = Some(*node);
+    while let Some(node) = current {
+        if let Some(color) = node.data().background_color() {
+            layers.push(color);
+            if color.alpha == 255 {
+                return layers
+                    .into_iter()
+                    .rev()
+                    .reduce(|bottom, top| blend(top, bottom));
+            }
+        }
+        current = node.parent();
+    }
+    None
+}
+
+/// The font size and weight of the node, inherited from its ancestors.
+fn effective_font(node: &AccessKitNode<'_>) -> (Option<f32>, Option<f32>) {
+    let mut size = None;
+    let mut weight = None;
+    let mut current = Some(*node);
+    while let Some(node) = current {
+        size = size.or(node.data().font_size());
+        weight = weight.or(node.data().font_weight());
+        current = node.parent();
+    }
+    (size, weight)
+}
+
+/// Large text (at least 18pt, or 14pt and bold) needs less contrast.
+/// AccessKit font sizes are in pixels, and 1pt is 4/3 px.
+fn is_large_text(size: Option<f32>, weight: Option<f32>) -> bool {
+    let Some(size) = size else {
+        return false;
+    };
+    let bold = weight.is_some_and(|weight| weight >= 700.0);
+    size >= 24.0 || (bold && size >= 14.0 * 4.0 / 3.0)
+}
+
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.red, color.green, color.blue)
+}
+
+/// Text needs enough contrast to its background.
+/// Nodes without children that have a name or value are checked.
+///
+/// Colors are taken from [`accesskit::Node::foreground_color`] and
+/// [`accesskit::Node::background_color`] of the node or its ancestors. Translucent backgrounds
+/// are blended over the backgrounds behind them. Nodes without a known foreground or opaque
+/// background are skipped.
+///
+/// Required contrast ratios:
+///
+/// | Level | Normal text | Large text |
+/// |-------|-------------|------------|
+/// | AA    | 4.5:1       | 3:1        |
+/// | AAA   | 7:1         | 4.5:1      |
+///
+/// Text is large if its [`accesskit::Node::font_size`] is at least 24px, or 18.66px with a
+/// [`accesskit::Node::font_weight`] of at least 700.
+#[derive(Clone, Copy, Debug)]
+pub struct ColorContrast {
+    level: WcagLevel,
+}
+
+impl Default for ColorContrast {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ColorContrast {
+    /// Check the AA thresholds (WCAG 1.4.3). This rule is part of the [`crate::default_rules`].
+    pub fn new() -> Self {
+        Self {
+            level: WcagLevel::AA,
+        }
+    }
+
+    /// Check the stricter AAA thresholds (WCAG 1.4.6).
+    ///
+    /// This is a separate rule (`color-contrast-enhanced`), so disable the AA rule when adding
+    /// it, or text failing both is reported twice:
+    ///
+    /// ```rust ignore
+    /// let config = AuditConfig::new()
+    ///     .disable("color-contrast")
+    ///     .rule(ColorContrast::enhanced());
+    /// ```
+    pub fn enhanced() -> Self {
+        Self {
+            level: WcagLevel::AAA,
+        }
+    }
+
+    fn required_ratio(&self, large_text: bool) -> f64 {
+        match (self.level, large_text) {
+            (WcagLevel::AAA, false) => 7.0,
+            (WcagLevel::AAA, true) | (WcagLevel::A | WcagLevel::AA, false) => 4.5,
+            (WcagLevel::A | WcagLevel::AA, true) => 3.0,
+        }
+    }
+}
+
+impl Rule for ColorContrast {
+    fn id(&self) -> &'static str {
+        match self.level {
+            WcagLevel::AAA => "color-contrast-enhanced",
+            WcagLevel::A | WcagLevel::AA => "color-contrast",
+        }
+    }
+
+    fn wcag(&self) -> &'static [WcagCriterion] {
+        match self.level {
+            WcagLevel::AAA => &[WcagCriterion::CONTRAST_ENHANCED],
+            WcagLevel::A | WcagLevel::AA => &[WcagCriterion::CONTRAST_MINIMUM],
+        }
+    }
+
+    fn check(&self, node: &AccessKitNode<'_>) -> Option<String> {
+        // Container names (e.g. of windows) aren't necessarily rendered, so only check leaves.
+        if node.children().next().is_some() || node_text(node).is_empty() || is_hidden_in_tree(node)
+        {
+            return None;
+        }
+        let background = effective_background(node)?;
+        let foreground = blend(effective_foreground(node)?, background);
+
+        let (size, weight) = effective_font(node);
+        let large_text = is_large_text(size, weight);
+        let required = self.required_ratio(large_text);
+        let ratio = contrast_ratio(foreground, background);
+        (ratio < required).then(|| {
+            format!(
+                "Contrast ratio {ratio:.2}:1 of {} on {} is below {required}:1 for {} text",
+                hex(foreground),
+                hex(background),
+                if large_text { "large" } else { "normal" }
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::State;
+    use crate::test_util::{node, state};
+    use accesskit::Role;
+
+    const WHITE: Color = Color {
+        red: 255,
+        green: 255,
+        blue: 255,
+        alpha: 255,
+    };
+
+    const fn gray(value: u8) -> Color {
+        Color {
+            red: value,
+            green: value,
+            blue: value,
+            alpha: 255,
+        }
+    }
+
+    /// A white window with a label in the given color and font.
+    fn label(color: Color, size: f32, weight: Option<f32>) -> State {
+        let mut window = node(Role::Window, None, &[1]);
+        window.set_background_color(WHITE);
+        let mut label = node(Role::Label, None, &[]);
+        label.set_value("Text");
+        label.set_foreground_color(color);
+        label.set_font_size(size);
+        if let Some(weight) = weight {
+            label.set_font_weight(weight);
+        }
+        state([(0, window), (1, label)])
+    }
+
+    fn check(rule: ColorContrast, state: &State) -> Option<String> {
+        let label = state.root().children().next().unwrap();
+        rule.check(&label)
+    }
+
+    #[test]
+    fn known_ratios() {
+        assert!((contrast_ratio(gray(0), WHITE) - 21.0).abs() < 1e-9);
+        assert!((contrast_ratio(WHITE, gray(0)) - 21.0).abs() < 1e-9);
+        assert!((contrast_ratio(WHITE, WHITE) - 1.0).abs() < 1e-9);
+        assert!((contrast_ratio(gray(0x77), WHITE) - 4.48).abs() < 0.005);
+        assert!((contrast_ratio(gray(0x76), WHITE) - 4.54).abs() < 0.005);
+    }
+
+    #[test]
+    fn normal_text_thresholds() {
+        assert_eq!(
+            check(ColorContrast::new(), &label(gray(0x76), 14.0, None)),
+            None
+        );
+        assert_eq!(
+            check(ColorContrast::new(), &label(gray(0x77), 14.0, None)).as_deref(),
+            Some("Contrast ratio 4.48:1 of #777777 on #ffffff is below 4.5:1 for normal text")
+        );
+        assert!(check(ColorContrast::enhanced(), &label(gray(0x59), 14.0, None)).is_none());
+        assert!(check(ColorContrast::enhanced(), &label(gray(0x5a), 14.0, None)).is_some());
+    }
+
+    #[test]
+    fn large_text_boundary() {
+        assert!(!is_large_text(None, Some(700.0)));
+        assert!(!is_large_text(Some(23.9), None));
+        assert!(is_large_text(Some(24.0), None));
+        assert!(!is_large_text(Some(18.6), Some(700.0)));
+        assert!(!is_large_text(Some(18.67), Some(600.0)));
+        assert!(is_large_text(Some(18.67), Some(700.0)));
+
+        // #777 on white passes for large text only.
+        let rule = ColorContrast::new();
+        assert!(check(rule, &label(gray(0x77), 23.9, None)).is_some());
+        assert!(check(rule, &label(gray(0x77), 24.0, None)).is_none());
+        assert!(check(rule, &label(gray(0x77), 18.6, Some(700.0))).is_some());
+        assert!(check(rule, &label(gray(0x77), 18.67, Some(700.0))).is_none());
+        assert!(check(ColorContrast::enhanced(), &label(gray(0x77), 24.0, None)).is_some());
+    }
+}
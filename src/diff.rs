@@ -0,0 +1,483 @@
+use crate::changes::{PropertyChange, diff_properties, node_properties_with};
+use crate::golden::longest_common_subsequence;
+use crate::node::accessible_name;
+use crate::redact::Redactions;
+use crate::snapshot::role_name;
+use crate::{AccessKitNode, NodeT};
+use accesskit::Role;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// How nodes of the old and new tree are paired in [`diff_trees`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NodePairing {
+    /// Pair nodes with the same AccessKit node id.
+    #[default]
+    Id,
+
+    /// Pair nodes with the same role and name, in tree order.
+    /// Use this if the ids aren't stable, e.g. when comparing trees from different runs.
+    RoleAndName,
+}
+
+/// Options for [`diff_trees_with_options`].
+#[derive(Clone, Debug)]
+pub struct TreeDiffOptions<'a> {
+    pairing: NodePairing,
+    redactions: Redactions<'a>,
+}
+
+impl Default for TreeDiffOptions<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> TreeDiffOptions<'a> {
+    /// Pair nodes by id and don't redact anything.
+    pub fn new() -> Self {
+        Self {
+            pairing: NodePairing::Id,
+            redactions: Redactions::new(),
+        }
+    }
+
+    /// How to pair the nodes of the old and new tree.
+    /// Default is [`NodePairing::Id`].
+    pub fn pairing(mut self, pairing: NodePairing) -> Self {
+        self.pairing = pairing;
+        self
+    }
+
+    /// Ignore volatile data, see [`Redactions`].
+    /// Default redacts nothing.
+    pub fn redactions(mut self, redactions: Redactions<'a>) -> Self {
+        self.redactions = redactions;
+        self
+    }
+}
+
+/// A node in a [`TreeDiff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffNode {
+    /// The local AccessKit node id, `None` if ids are redacted.
+    pub id: Option<u64>,
+    pub role: Role,
+    /// The label, or the value for nodes like `Role::Label`.
+    pub name: Option<String>,
+}
+
+impl Display for DiffNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", role_name(self.role))?;
+        if let Some(name) = &self.name {
+            write!(f, " {name:?}")?;
+        }
+        if let Some(id) = self.id {
+            write!(f, " (#{id})")?;
+        }
+        Ok(())
+    }
+}
+
+/// An inserted or removed node, and its parent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffEntry {
+    pub node: DiffNode,
+    pub parent: Option<DiffNode>,
+}
+
+/// A node that has a different parent, or a different position among its siblings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffMove {
+    pub node: DiffNode,
+    pub old_parent: Option<DiffNode>,
+    pub new_parent: Option<DiffNode>,
+}
+
+/// A node with changed properties.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffChange {
+    pub node: DiffNode,
+    pub changes: Vec<PropertyChange>,
+}
+
+/// The structural difference between two trees, see [`diff_trees`].
+///
+/// Renders as a list of changes, or with colors via [`TreeDiff::to_colored_string`]:
+/// ```text
+/// + button "Undo" in window "App"
+/// - label "Loading…" in window "App"
+/// ~ label "Saved"
+///     value: "Idle" -> "Saved"
+/// > button "Save" moved from group "Toolbar" to group "Footer"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TreeDiff {
+    pub inserted: Vec<DiffEntry>,
+    pub removed: Vec<DiffEntry>,
+    pub moved: Vec<DiffMove>,
+    pub changed: Vec<DiffChange>,
+}
+
+impl TreeDiff {
+    /// Returns true if the trees are equal.
+    pub fn is_empty(&self) -> bool {
+        self.inserted.is_empty()
+            && self.removed.is_empty()
+            && self.moved.is_empty()
+            && self.changed.is_empty()
+    }
+
+    /// Render the diff with ANSI colors, e.g. for terminal output.
+    pub fn to_colored_string(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, true)
+            .expect("Writing to a String can't fail");
+        out
+    }
+
+    fn write(&self, out: &mut impl std::fmt::Write, color: bool) -> std::fmt::Result {
+        const GREEN: &str = "\x1b[32m";
+        const RED: &str = "\x1b[31m";
+        const YELLOW: &str = "\x1b[33m";
+        const CYAN: &str = "\x1b[36m";
+        const RESET: &str = "\x1b[0m";
+        let paint = |code: &'static str| if color { code } else { "" };
+        let reset = paint(RESET);
+        let fmt_parent = |parent: &Option<DiffNode>| {
+            parent
+                .as_ref()
+                .map_or_else(|| "nothing".to_owned(), ToString::to_string)
+        };
+
+        if self.is_empty() {
+            return writeln!(out, "No differences");
+        }
+        for insertion in &self.inserted {
+            write!(out, "{}+ {}", paint(GREEN), insertion.node)?;
+            if let Some(parent) = &insertion.parent {
+                write!(out, " in {parent}")?;
+            }
+            writeln!(out, "{reset}")?;
+        }
+        for removal in &self.removed {
+            write!(out, "{}- {}", paint(RED), removal.node)?;
+            if let Some(parent) = &removal.parent {
+                write!(out, " in {parent}")?;
+            }
+            writeln!(out, "{reset}")?;
+        }
+        for changed in &self.changed {
+            writeln!(out, "{}~ {}{reset}", paint(YELLOW), changed.node)?;
+            for change in &changed.changes {
+                writeln!(out, "    {change}")?;
+            }
+        }
+        for moved in &self.moved {
+            write!(out, "{}> {}", paint(CYAN), moved.node)?;
+            if moved.old_parent == moved.new_parent {
+                write!(out, " reordered in {}", fmt_parent(&moved.new_parent))?;
+            } else {
+                write!(
+                    out,
+                    " moved from {} to {}",
+                    fmt_parent(&moved.old_parent),
+                    fmt_parent(&moved.new_parent)
+                )?;
+            }
+            writeln!(out, "{reset}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for TreeDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write(f, false)
+    }
+}
+
+/// Identifies a node across the two trees.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Key {
+    Id(u64),
+    /// Role, name and the number of previous nodes with the same role and name.
+    RoleAndName(Role, Option<String>, usize),
+}
+
+struct Entry {
+    key: Key,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    node: DiffNode,
+    properties: Vec<(&'static str, String)>,
+}
+
+/// The nodes of a tree in tree order.
+struct FlatTree {
+    entries: Vec<Entry>,
+    index: HashMap<Key, usize>,
+}
+
+impl FlatTree {
+    fn new(root: &AccessKitNode<'_>, options: &TreeDiffOptions<'_>) -> Self {
+        let mut tree = Self {
+            entries: Vec::new(),
+            index: HashMap::new(),
+        };
+        let mut occurrences = HashMap::new();
+        tree.push(root, None, options, &mut occurrences);
+        tree
+    }
+
+    fn push(
+        &mut self,
+        node: &AccessKitNode<'_>,
+        parent: Option<usize>,
+        options: &TreeDiffOptions<'_>,
+        occurrences: &mut HashMap<(Role, Option<String>), usize>,
+    ) {
+        let redactions = &options.redactions;
+        if redactions.is_skipped(node) {
+            return;
+        }
+
+        let name = if node.label_comes_from_value() {
+            redactions.value(node)
+        } else {
+            accessible_name(node)
+        };
+        let id = node.locate().0.0;
+        let key = match options.pairing {
+            NodePairing::Id => Key::Id(id),
+            NodePairing::RoleAndName => {
+                let occurrence = occurrences.entry((node.role(), name.clone())).or_default();
+                *occurrence += 1;
+                Key::RoleAndName(node.role(), name.clone(), *occurrence)
+            }
+        };
+
+        let index = self.entries.len();
+        self.index.insert(key.clone(), index);
+        self.entries.push(Entry {
+            key,
+            parent,
+            children: Vec::new(),
+            node: DiffNode {
+                id: redactions.show_ids().then_some(id),
+                role: node.role(),
+                name,
+            },
+            properties: node_properties_with(node, redactions)
+                .into_iter()
+                .filter(|(property, _)| *property != "children")
+                .collect(),
+        });
+        if let Some(parent) = parent.and_then(|parent| self.entries.get_mut(parent)) {
+            parent.children.push(index);
+        }
+
+        for child in node.children() {
+            self.push(&child, Some(index), options, occurrences);
+        }
+    }
+
+    fn parent_key(&self, entry: &Entry) -> Option<&Key> {
+        entry
+            .parent
+            .and_then(|parent| self.entries.get(parent))
+            .map(|parent| &parent.key)
+    }
+
+    fn parent_node(&self, entry: &Entry) -> Option<DiffNode> {
+        entry
+            .parent
+            .and_then(|parent| self.entries.get(parent))
+            .map(|parent| parent.node.clone())
+    }
+}
+
+/// Compare two trees, pairing nodes by id. See [`diff_trees_with_options`].
+///
+/// ```rust ignore
+/// let old = State::new(old_update);
+/// let diff = diff_trees(&TreeNode::new(old.root()), &TreeNode::new(harness.state().root()));
+/// assert!(diff.is_empty(), "Unexpected changes:\n{}", diff.to_colored_string());
+/// ```
+pub fn diff_trees<'old, 'new, Old, New>(old: &Old, new: &New) -> TreeDiff
+where
+    Old: NodeT<'old> + 'old,
+    New: NodeT<'new> + 'new,
+{
+    diff_trees_with_options(old, new, &TreeDiffOptions::default())
+}
+
+/// Compare two trees and report inserted, removed, moved and changed nodes.
+///
+/// A node is moved if its parent changed, or if its position among the siblings that exist in
+/// both trees changed.
+pub fn diff_trees_with_options<'old, 'new, Old, New>(
+    old: &Old,
+    new: &New,
+    options: &TreeDiffOptions<'_>,
+) -> TreeDiff
+where
+    Old: NodeT<'old> + 'old,
+    New: NodeT<'new> + 'new,
+{
+    let old = FlatTree::new(&old.accesskit_node(), options);
+    let new = FlatTree::new(&new.accesskit_node(), options);
+    let mut diff = TreeDiff::default();
+
+    for entry in &new.entries {
+        if !old.index.contains_key(&entry.key) {
+            diff.inserted.push(DiffEntry {
+                node: entry.node.clone(),
+                parent: new.parent_node(entry),
+            });
+        }
+    }
+
+    for old_entry in &old.entries {
+        let Some(new_entry) = new
+            .index
+            .get(&old_entry.key)
+            .and_then(|index| new.entries.get(*index))
+        else {
+            diff.removed.push(DiffEntry {
+                node: old_entry.node.clone(),
+                parent: old.parent_node(old_entry),
+            });
+            continue;
+        };
+
+        let changes = diff_properties(&old_entry.properties, &new_entry.properties);
+        if !changes.is_empty() {
+            diff.changed.push(DiffChange {
+                node: new_entry.node.clone(),
+                changes,
+            });
+        }
+
+        if old.parent_key(old_entry) != new.parent_key(new_entry) {
+            diff.moved.push(DiffMove {
+                node: new_entry.node.clone(),
+                old_parent: old.parent_node(old_entry),
+                new_parent: new.parent_node(new_entry),
+            });
+            continue;
+        }
+
+        // Find children that stayed with this parent but changed their order.
+        let stayed = |tree: &FlatTree, other: &FlatTree, entry: &Entry| -> Vec<Key> {
+            entry
+                .children
+                .iter()
+                .filter_map(|child| tree.entries.get(*child))
+                .filter(|child| {
+                    other
+                        .index
+                        .get(&child.key)
+                        .and_then(|index| other.entries.get(*index))
+                        .is_some_and(|other_child| {
+                            other.parent_key(other_child) == Some(&entry.key)
+                        })
+                })
+                .map(|child| child.key.clone())
+                .collect()
+        };
+        let old_children = stayed(&old, &new, old_entry);
+        let new_children = stayed(&new, &old, new_entry);
+        let in_order = longest_common_subsequence(&old_children, &new_children);
+        for (index, key) in new_children.iter().enumerate() {
+            if !in_order.iter().any(|(_, new_index)| *new_index == index)
+                && let Some(child) = new.index.get(key).and_then(|i| new.entries.get(*i))
+            {
+                diff.moved.push(DiffMove {
+                    node: child.node.clone(),
+                    old_parent: Some(old_entry.node.clone()),
+                    new_parent: Some(new_entry.node.clone()),
+                });
+            }
+        }
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{node, state};
+    use crate::{State, TreeNode};
+
+    fn diff(old: &State, new: &State, pairing: NodePairing) -> String {
+        let options = TreeDiffOptions::new().pairing(pairing);
+        diff_trees_with_options(
+            &TreeNode::new(old.root()),
+            &TreeNode::new(new.root()),
+            &options,
+        )
+        .to_string()
+    }
+
+    /// A window with a button for each `(id, label)`.
+    fn buttons(buttons: &[(u64, &str)]) -> State {
+        let ids: Vec<u64> = buttons.iter().map(|(id, _)| *id).collect();
+        state(
+            std::iter::once((0, node(Role::Window, Some("App"), &ids))).chain(
+                buttons
+                    .iter()
+                    .map(|(id, label)| (*id, node(Role::Button, Some(label), &[]))),
+            ),
+        )
+    }
+
+    /// The "Toolbar" and "Footer" groups with the given buttons.
+    fn groups(toolbar: &[u64], footer: &[u64]) -> State {
+        state([
+            (0, node(Role::Window, Some("App"), &[1, 2])),
+            (1, node(Role::Group, Some("Toolbar"), toolbar)),
+            (2, node(Role::Group, Some("Footer"), footer)),
+            (3, node(Role::Button, Some("Save"), &[])),
+            (4, node(Role::Button, Some("Undo"), &[])),
+        ])
+    }
+
+    #[test]
+    fn role_and_name_pairs_by_occurrence() {
+        let old = buttons(&[(1, "Item"), (2, "Item"), (3, "Other")]);
+        let new = buttons(&[(10, "Item"), (11, "Other")]);
+        assert_eq!(
+            diff(&old, &new, NodePairing::Id).lines().count(),
+            5,
+            "Every node has a new id"
+        );
+        // The first "Item" is paired with the first one, so the second one is removed.
+        assert_eq!(
+            diff(&old, &new, NodePairing::RoleAndName),
+            "- button \"Item\" (#2) in window \"App\" (#0)\n"
+        );
+    }
+
+    #[test]
+    fn moved_node() {
+        assert_eq!(
+            diff(&groups(&[3, 4], &[]), &groups(&[4], &[3]), NodePairing::Id),
+            "> button \"Save\" (#3) moved from group \"Toolbar\" (#1) to group \"Footer\" (#2)\n"
+        );
+    }
+
+    #[test]
+    fn reordered_node() {
+        assert_eq!(
+            diff(
+                &groups(&[3, 4], &[]),
+                &groups(&[4, 3], &[]),
+                NodePairing::Id
+            ),
+            "> button \"Save\" (#3) reordered in group \"Toolbar\" (#1)\n"
+        );
+    }
+}
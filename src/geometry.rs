@@ -0,0 +1,429 @@
+use crate::AccessKitNode;
+use crate::audit::{Rule, Severity, WcagCriterion, is_hidden_in_tree, visit};
+use crate::changes::NodeSummary;
+use crate::rules::is_interactive;
+use accesskit::{Rect, Role};
+use std::collections::HashMap;
+
+fn fmt_rect(rect: Rect) -> String {
+    format!("{},{} {}x{}", rect.x0, rect.y0, rect.width(), rect.height())
+}
+
+/// The global bounds of a visible, enabled interactive node.
+fn target_bounds(node: &AccessKitNode<'_>) -> Option<Rect> {
+    if !is_interactive(node) || node.is_disabled() || is_hidden_in_tree(node) {
+        return None;
+    }
+    node.bounding_box()
+}
+
+/// Nested interactive nodes (e.g. a button in a clickable list item) are allowed to overlap.
+fn is_nested(a: &AccessKitNode<'_>, b: &AccessKitNode<'_>) -> bool {
+    a.is_descendant_of(b) || b.is_descendant_of(a)
+}
+
+/// The targets (see [`target_bounds`]) of a tree in tree order, bucketed into a grid of square
+/// cells, so nearby targets can be found without comparing every pair.
+struct Targets<'tree> {
+    targets: Vec<(AccessKitNode<'tree>, Rect)>,
+    cell_size: f64,
+    grid: HashMap<(i64, i64), Vec<usize>>,
+    /// Targets covering too many cells (or with non-finite bounds), which are near everything.
+    large: Vec<usize>,
+}
+
+impl<'tree> Targets<'tree> {
+    /// Targets covering more cells than this are kept in [`Self::large`].
+    const MAX_CELLS: f64 = 1024.0;
+
+    fn new(root: &AccessKitNode<'tree>, cell_size: f64) -> Self {
+        let mut targets = Self {
+            targets: Vec::new(),
+            cell_size: if cell_size > 0.0 { cell_size } else { 24.0 },
+            grid: HashMap::new(),
+            large: Vec::new(),
+        };
+        visit(root, &mut |node| {
+            if let Some(bounds) = target_bounds(node) {
+                let index = targets.targets.len();
+                targets.targets.push((*node, bounds));
+                match targets.cells(bounds) {
+                    Some(cells) => {
+                        for cell in cells {
+                            targets.grid.entry(cell).or_default().push(index);
+                        }
+                    }
+                    None => targets.large.push(index),
+                }
+            }
+        });
+        targets
+    }
+
+    /// The grid cells covered by `rect`, or `None` if there are too many.
+    #[allow(clippy::cast_possible_truncation)] // The cell range is checked first.
+    fn cells(&self, rect: Rect) -> Option<impl Iterator<Item = (i64, i64)> + use<>> {
+        let x = (
+            (rect.x0 / self.cell_size).floor(),
+            (rect.x1 / self.cell_size).floor(),
+        );
+        let y = (
+            (rect.y0 / self.cell_size).floor(),
+            (rect.y1 / self.cell_size).floor(),
+        );
+        let count = (x.1 - x.0 + 1.0) * (y.1 - y.0 + 1.0);
+        if !count.is_finite() || count > Self::MAX_CELLS {
+            return None;
+        }
+        let (x, y) = ((x.0 as i64, x.1 as i64), (y.0 as i64, y.1 as i64));
+        Some((x.0..=x.1).flat_map(move |cell_x| (y.0..=y.1).map(move |cell_y| (cell_x, cell_y))))
+    }
+
+    /// The indices of the targets whose bounds may intersect `rect`, in tree order.
+    fn near(&self, rect: Rect) -> Vec<usize> {
+        let mut near = self.large.clone();
+        match self.cells(rect) {
+            Some(cells) => {
+                for cell in cells {
+                    near.extend(self.grid.get(&cell).into_iter().flatten());
+                }
+            }
+            None => near.extend(0..self.targets.len()),
+        }
+        near.sort_unstable();
+        near.dedup();
+        near
+    }
+
+    fn get(&self, index: usize) -> Option<&(AccessKitNode<'tree>, Rect)> {
+        self.targets.get(index)
+    }
+}
+
+fn rect_center(rect: Rect) -> (f64, f64) {
+    ((rect.x0 + rect.x1) / 2.0, (rect.y0 + rect.y1) / 2.0)
+}
+
+/// Returns true if the circle intersects the rect (touching doesn't count).
+fn circle_intersects_rect(center: (f64, f64), radius: f64, rect: Rect) -> bool {
+    let dx = (rect.x0 - center.0).max(center.0 - rect.x1).max(0.0);
+    let dy = (rect.y0 - center.1).max(center.1 - rect.y1).max(0.0);
+    dx * dx + dy * dy < radius * radius
+}
+
+/// Interactive nodes need to be big enough to hit reliably.
+///
+/// The minimum is 24x24, as required by WCAG 2.5.8. Disabled and hidden nodes and nodes
+/// without bounds are skipped. So are the exceptions of WCAG 2.5.8:
+/// - spacing: a smaller target is fine if a circle with a diameter of the minimum size,
+///   centered on the target, doesn't intersect another target or the circle of another smaller
+///   target
+/// - inline: links in a paragraph are sized by the surrounding text
+///
+/// The default severity is [`Severity::Warning`], since many toolkits (including egui) use
+/// smaller controls by default. Use [`crate::AuditConfig::severity`] to make it an error.
+#[derive(Clone, Copy, Debug)]
+pub struct TargetSize {
+    min_width: f64,
+    min_height: f64,
+}
+
+impl Default for TargetSize {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TargetSize {
+    /// Require at least 24x24.
+    pub fn new() -> Self {
+        Self {
+            min_width: 24.0,
+            min_height: 24.0,
+        }
+    }
+
+    /// Require a different minimum size, in the units of the node bounds.
+    ///
+    /// ```rust ignore
+    /// // Replaces the default 24x24 check.
+    /// let config = AuditConfig::new().rule(TargetSize::new().min_size(44.0, 44.0));
+    /// ```
+    pub fn min_size(mut self, width: f64, height: f64) -> Self {
+        self.min_width = width;
+        self.min_height = height;
+        self
+    }
+
+    fn is_undersized(&self, bounds: Rect) -> bool {
+        bounds.width() < self.min_width || bounds.height() < self.min_height
+    }
+
+    fn is_inline(node: &AccessKitNode<'_>) -> bool {
+        node.role() == Role::Link
+            && node
+                .parent()
+                .is_some_and(|parent| parent.role() == Role::Paragraph)
+    }
+}
+
+impl Rule for TargetSize {
+    fn id(&self) -> &'static str {
+        "target-size"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn wcag(&self) -> &'static [WcagCriterion] {
+        &[WcagCriterion::TARGET_SIZE_MINIMUM]
+    }
+
+    fn check_tree<'tree>(
+        &self,
+        root: &AccessKitNode<'tree>,
+    ) -> Vec<(AccessKitNode<'tree>, String)> {
+        let diameter = self.min_width.max(self.min_height);
+        let targets = Targets::new(root, diameter);
+        let mut problems = Vec::new();
+        for (index, (node, bounds)) in targets.targets.iter().enumerate() {
+            if !self.is_undersized(*bounds) || Self::is_inline(node) {
+                continue;
+            }
+            let center = rect_center(*bounds);
+            // Other targets closer than `diameter` to the center, or with a circle intersecting
+            // this one, intersect this box.
+            let search = Rect::new(
+                center.0 - diameter,
+                center.1 - diameter,
+                center.0 + diameter,
+                center.1 + diameter,
+            );
+            let crowded_by = targets.near(search).into_iter().find_map(|other| {
+                let (other_node, other_bounds) = targets.get(other)?;
+                let (other_x, other_y) = rect_center(*other_bounds);
+                let crowded = other != index
+                    && !is_nested(node, other_node)
+                    && (circle_intersects_rect(center, diameter / 2.0, *other_bounds)
+                        || (self.is_undersized(*other_bounds)
+                            && (other_x - center.0).hypot(other_y - center.1) < diameter));
+                crowded.then_some((other_node, other_bounds))
+            });
+            if let Some((other, other_bounds)) = crowded_by {
+                problems.push((
+                    *node,
+                    format!(
+                        "Target {} is smaller than {}x{} and too close to {} at {}",
+                        fmt_rect(*bounds),
+                        self.min_width,
+                        self.min_height,
+                        NodeSummary::new(other),
+                        fmt_rect(*other_bounds)
+                    ),
+                ));
+            }
+        }
+        problems
+    }
+}
+
+/// Interactive nodes shouldn't overlap, that usually means a layout bug.
+///
+/// Each overlapping pair is reported once, on the later node in tree order. Nested interactive
+/// nodes (e.g. a button in a clickable list item) are allowed to overlap.
+pub struct OverlappingTargets;
+
+impl Rule for OverlappingTargets {
+    fn id(&self) -> &'static str {
+        "overlapping-targets"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_tree<'tree>(
+        &self,
+        root: &AccessKitNode<'tree>,
+    ) -> Vec<(AccessKitNode<'tree>, String)> {
+        let targets = Targets::new(root, 24.0);
+        let mut problems = Vec::new();
+        for (index, (node, bounds)) in targets.targets.iter().enumerate() {
+            let overlapping: Vec<_> = targets
+                .near(*bounds)
+                .into_iter()
+                .take_while(|other| *other < index)
+                .filter_map(|other| targets.get(other))
+                .filter(|(other, other_bounds)| {
+                    !is_nested(node, other) && bounds.intersect(*other_bounds).area() > 0.0
+                })
+                .map(|(other, rect)| format!("{} at {}", NodeSummary::new(other), fmt_rect(*rect)))
+                .collect();
+            if !overlapping.is_empty() {
+                problems.push((
+                    *node,
+                    format!(
+                        "Target {} overlaps {}",
+                        fmt_rect(*bounds),
+                        overlapping.join(", ")
+                    ),
+                ));
+            }
+        }
+        problems
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+    use crate::test_util::{node, state};
+    use crate::{State, audit_node};
+
+    /// A window with a button for each rect. The buttons after the first `top_level` ones are
+    /// children of the first button.
+    fn buttons(rects: &[Rect], top_level: usize) -> State {
+        let ids: Vec<u64> = (1..=rects.len() as u64).collect();
+        let (top, inner) = ids.split_at(top_level.min(ids.len()));
+        let mut nodes = vec![(0, node(Role::Window, None, top))];
+        for (id, rect) in ids.iter().zip(rects) {
+            let children = if *id == 1 { inner } else { &[] };
+            let mut button = node(Role::Button, Some(&format!("Button {id}")), children);
+            button.set_bounds(*rect);
+            nodes.push((*id, button));
+        }
+        state(nodes)
+    }
+
+    fn violations(rule: impl Rule + 'static, state: &State) -> Vec<(u64, String)> {
+        audit_node(&state.root(), &[Box::new(rule)])
+            .into_iter()
+            .map(|violation| (violation.node.id.0, violation.message))
+            .collect()
+    }
+
+    #[test]
+    fn large_targets_pass() {
+        let state = buttons(&[Rect::new(0.0, 0.0, 80.0, 24.0)], 1);
+        assert!(violations(TargetSize::new(), &state).is_empty());
+        assert_eq!(TargetSize::new().severity(), Severity::Warning);
+    }
+
+    #[test]
+    fn spaced_small_targets_pass() {
+        // 18px high, with 24px between the centers.
+        let state = buttons(
+            &[
+                Rect::new(0.0, 0.0, 80.0, 18.0),
+                Rect::new(0.0, 24.0, 80.0, 42.0),
+            ],
+            2,
+        );
+        assert!(violations(TargetSize::new(), &state).is_empty());
+    }
+
+    #[test]
+    fn crowded_small_targets_fail() {
+        // 18px high, with 22px between the centers.
+        let state = buttons(
+            &[
+                Rect::new(0.0, 0.0, 80.0, 18.0),
+                Rect::new(0.0, 22.0, 80.0, 40.0),
+            ],
+            2,
+        );
+        assert_eq!(
+            violations(TargetSize::new(), &state),
+            [
+                (
+                    1,
+                    "Target 0,0 80x18 is smaller than 24x24 and too close to button \"Button 2\" (#2) at 0,22 80x18".to_owned()
+                ),
+                (
+                    2,
+                    "Target 0,22 80x18 is smaller than 24x24 and too close to button \"Button 1\" (#1) at 0,0 80x18".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn small_target_next_to_a_large_one_fails() {
+        // The circle around the small button reaches into the large one.
+        let state = buttons(
+            &[
+                Rect::new(0.0, 0.0, 16.0, 16.0),
+                Rect::new(18.0, 0.0, 100.0, 30.0),
+            ],
+            2,
+        );
+        let violations = violations(TargetSize::new(), &state);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].0, 1);
+    }
+
+    #[test]
+    fn inline_links_pass() {
+        let mut nodes = vec![
+            (0, node(Role::Window, None, &[1])),
+            (1, node(Role::Paragraph, None, &[2, 3])),
+        ];
+        for (id, x) in [(2, 0.0), (3, 20.0)] {
+            let mut link = node(Role::Link, Some("link"), &[]);
+            link.set_bounds(Rect::new(x, 0.0, x + 16.0, 14.0));
+            nodes.push((id, link));
+        }
+        let state = state(nodes);
+        assert!(violations(TargetSize::new(), &state).is_empty());
+    }
+
+    #[test]
+    fn overlapping_targets_are_reported_once() {
+        let state = buttons(
+            &[
+                Rect::new(0.0, 0.0, 50.0, 30.0),
+                Rect::new(40.0, 0.0, 90.0, 30.0),
+                Rect::new(100.0, 0.0, 150.0, 30.0),
+            ],
+            3,
+        );
+        assert_eq!(
+            violations(OverlappingTargets, &state),
+            [(
+                2,
+                "Target 40,0 50x30 overlaps button \"Button 1\" (#1) at 0,0 50x30".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn nested_targets_may_overlap() {
+        let state = buttons(
+            &[
+                Rect::new(0.0, 0.0, 200.0, 30.0),
+                Rect::new(150.0, 0.0, 190.0, 30.0),
+            ],
+            1,
+        );
+        assert!(violations(OverlappingTargets, &state).is_empty());
+    }
+
+    #[test]
+    fn huge_targets() {
+        let state = buttons(
+            &[
+                Rect::new(-1e12, -1e12, 1e12, 1e12),
+                Rect::new(0.0, 0.0, 10.0, f64::INFINITY),
+                Rect::new(5.0, 5.0, 30.0, 30.0),
+            ],
+            3,
+        );
+        let overlapping: Vec<_> = violations(OverlappingTargets, &state)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(overlapping, [2, 3]);
+    }
+}
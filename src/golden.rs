@@ -0,0 +1,442 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Set this environment variable to `1` to update snapshot files instead of comparing against
+/// them.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "KITTEST_UPDATE_SNAPSHOTS";
+
+/// Assert that a tree matches the snapshot file stored next to the test.
+///
+/// The snapshot is created with [`crate::snapshot_with_options`] and stored in
+/// `snapshots/<name>.snap`, in the directory of the file calling the macro.
+/// The first argument can be anything implementing [`crate::Queryable`], e.g. your harness or
+/// a node.
+///
+/// If the snapshot doesn't match, the new snapshot is written to `snapshots/<name>.snap.new`
+/// and the test fails with a diff. Run the tests with `KITTEST_UPDATE_SNAPSHOTS=1` to accept
+/// the new snapshots.
+///
+/// ```rust ignore
+/// assert_tree_snapshot!("settings_panel", harness);
+/// assert_tree_snapshot!("settings_panel_ids", harness, &SnapshotOptions::new().ids(true));
+/// ```
+#[macro_export]
+macro_rules! assert_tree_snapshot {
+    ($name:expr, $root:expr) => {
+        $crate::assert_tree_snapshot!($name, $root, &$crate::SnapshotOptions::default())
+    };
+    ($name:expr, $root:expr, $options:expr) => {
+        $crate::assert_snapshot_file(
+            &$crate::snapshot_dir(env!("CARGO_MANIFEST_DIR"), file!()),
+            $name,
+            &$crate::snapshot_with_options(&$crate::Queryable::queryable_node(&$root), $options),
+        )
+    };
+}
+
+/// An error when comparing against a snapshot file.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// There was no snapshot file yet.
+    Missing { path: PathBuf },
+
+    /// The snapshot didn't match the snapshot file.
+    Mismatch { path: PathBuf, diff: String },
+
+    /// Reading or writing a snapshot file failed.
+    Io { path: PathBuf, err: std::io::Error },
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing { path } => write!(
+                f,
+                "Missing snapshot file {}. Run with {UPDATE_SNAPSHOTS_ENV}=1 to create it.",
+                path.display()
+            ),
+            Self::Mismatch { path, diff } => write!(
+                f,
+                "Snapshot {} doesn't match. Run with {UPDATE_SNAPSHOTS_ENV}=1 to update it.\n\n{diff}",
+                path.display()
+            ),
+            Self::Io { path, err } => write!(f, "Failed to access {}: {err}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { err, .. } => Some(err),
+            Self::Missing { .. } | Self::Mismatch { .. } => None,
+        }
+    }
+}
+
+/// Get the `snapshots` directory next to a source file.
+///
+/// `file` is the path returned by `file!()`, which is relative to the workspace root, while
+/// `manifest_dir` is the crate root. This is used by [`assert_tree_snapshot`].
+pub fn snapshot_dir(manifest_dir: &str, file: &str) -> PathBuf {
+    let manifest_dir = Path::new(manifest_dir);
+    let file_dir = Path::new(file).parent().unwrap_or_else(|| Path::new(""));
+
+    let base = manifest_dir
+        .ancestors()
+        .find(|ancestor| ancestor.join(file).exists())
+        .unwrap_or(manifest_dir);
+
+    base.join(file_dir).join("snapshots")
+}
+
+fn should_update_snapshots() -> bool {
+    std::env::var(UPDATE_SNAPSHOTS_ENV).is_ok_and(|value| value == "1" || value == "true")
+}
+
+/// Compare `actual` against the snapshot file `<dir>/<name>.snap`.
+///
+/// If [`UPDATE_SNAPSHOTS_ENV`] is set, the snapshot file is written instead.
+/// Otherwise, a mismatching snapshot is written to `<dir>/<name>.snap.new`, so it can be reviewed.
+///
+/// # Errors
+/// Returns an error if the snapshot is missing or doesn't match, or if accessing the files fails.
+pub fn try_snapshot_file(dir: &Path, name: &str, actual: &str) -> Result<(), SnapshotError> {
+    let path = dir.join(format!("{name}.snap"));
+    let new_path = dir.join(format!("{name}.snap.new"));
+    let io_err = |path: &Path| {
+        let path = path.to_owned();
+        move |err| SnapshotError::Io { path, err }
+    };
+
+    let expected = match std::fs::read_to_string(&path) {
+        Ok(expected) => Some(expected),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => return Err(SnapshotError::Io { path, err }),
+    };
+
+    if expected.as_deref() == Some(actual) {
+        if new_path.exists() {
+            std::fs::remove_file(&new_path).map_err(io_err(&new_path))?;
+        }
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(dir).map_err(io_err(dir))?;
+
+    if should_update_snapshots() {
+        std::fs::write(&path, actual).map_err(io_err(&path))?;
+        if new_path.exists() {
+            std::fs::remove_file(&new_path).map_err(io_err(&new_path))?;
+        }
+        return Ok(());
+    }
+
+    std::fs::write(&new_path, actual).map_err(io_err(&new_path))?;
+
+    match expected {
+        Some(expected) => Err(SnapshotError::Mismatch {
+            path,
+            diff: unified_diff(&expected, actual),
+        }),
+        None => Err(SnapshotError::Missing { path }),
+    }
+}
+
+/// Assert that `actual` matches the snapshot file `<dir>/<name>.snap`.
+///
+/// See [`try_snapshot_file`].
+///
+/// # Panics
+/// - if the snapshot is missing or doesn't match, or if accessing the files fails.
+#[track_caller]
+pub fn assert_snapshot_file(dir: &Path, name: &str, actual: &str) {
+    if let Err(err) = try_snapshot_file(dir, name, actual) {
+        panic!("{err}");
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DiffLine<'a> {
+    Equal(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// The index pairs of the longest common subsequence of two sequences.
+///
+/// The common prefix and suffix are matched directly, the rest uses Hirschberg's algorithm,
+/// so the memory use is linear in the length of the sequences.
+#[allow(clippy::indexing_slicing)] // The prefix and suffix are bounded by both lengths.
+pub(crate) fn longest_common_subsequence<T: PartialEq>(
+    old: &[T],
+    new: &[T],
+) -> Vec<(usize, usize)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut pairs: Vec<_> = (0..prefix).map(|i| (i, i)).collect();
+    hirschberg(
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+        (prefix, prefix),
+        &mut pairs,
+    );
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    pairs.extend((0..suffix).map(|i| (old_end + i, new_end + i)));
+    pairs
+}
+
+/// The lengths of the longest common subsequences of `old` and every prefix of `new`,
+/// computed with two rows of the table.
+#[allow(clippy::indexing_slicing)] // The indices are bounded by the row length.
+fn lcs_lengths<'a, T: PartialEq + 'a>(
+    old: impl Iterator<Item = &'a T>,
+    new: &[&'a T],
+) -> Vec<usize> {
+    let mut previous = vec![0; new.len() + 1];
+    let mut current = vec![0; new.len() + 1];
+    for a in old {
+        for (j, b) in new.iter().enumerate() {
+            current[j + 1] = if a == *b {
+                previous[j] + 1
+            } else {
+                previous[j + 1].max(current[j])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous
+}
+
+/// Push the index pairs of the longest common subsequence of `old` and `new`, offset by `offset`.
+#[allow(clippy::indexing_slicing)] // `mid` and `split` are bounded by the slice lengths.
+fn hirschberg<T: PartialEq>(
+    old: &[T],
+    new: &[T],
+    offset: (usize, usize),
+    pairs: &mut Vec<(usize, usize)>,
+) {
+    if old.is_empty() || new.is_empty() {
+        return;
+    }
+    if let [line] = old {
+        if let Some(j) = new.iter().position(|other| other == line) {
+            pairs.push((offset.0, offset.1 + j));
+        }
+        return;
+    }
+
+    // Split `old` in half and find where the optimal path crosses the middle in `new`.
+    let mid = old.len() / 2;
+    let forward: Vec<_> = new.iter().collect();
+    let backward: Vec<_> = new.iter().rev().collect();
+    let head = lcs_lengths(old[..mid].iter(), &forward);
+    let tail = lcs_lengths(old[mid..].iter().rev(), &backward);
+    let split = (0..=new.len())
+        .max_by_key(|&j| (head[j] + tail[new.len() - j], std::cmp::Reverse(j)))
+        .unwrap_or_default();
+
+    hirschberg(&old[..mid], &new[..split], offset, pairs);
+    hirschberg(
+        &old[mid..],
+        &new[split..],
+        (offset.0 + mid, offset.1 + split),
+        pairs,
+    );
+}
+
+/// Diff two texts line by line, using the longest common subsequence.
+///
+/// The lines keep their `\n`, so a missing newline at the end of a text is a difference too.
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<_> = old.split_inclusive('\n').collect();
+    let new: Vec<_> = new.split_inclusive('\n').collect();
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let end = (old.len(), new.len());
+    for (old_index, new_index) in longest_common_subsequence(&old, &new)
+        .into_iter()
+        .chain(std::iter::once(end))
+    {
+        let removed = old.get(i..old_index).unwrap_or_default();
+        let added = new.get(j..new_index).unwrap_or_default();
+        lines.extend(removed.iter().map(|line| DiffLine::Removed(line)));
+        lines.extend(added.iter().map(|line| DiffLine::Added(line)));
+        lines.extend(old.get(old_index).map(|line| DiffLine::Equal(line)));
+        (i, j) = (old_index + 1, new_index + 1);
+    }
+    lines
+}
+
+/// Create a unified diff (with 3 lines of context) between two texts.
+///
+/// Like `diff -u`, a last line without a newline is followed by `\ No newline at end of file`.
+pub fn unified_diff(old: &str, new: &str) -> String {
+    const CONTEXT: usize = 3;
+
+    let lines = diff_lines(old, new);
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Equal(_)))
+        .map(|(index, _)| index)
+        .collect();
+
+    // Group the changes into hunks, merging hunks whose context overlaps.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for index in changed {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = String::from("--- expected\n+++ actual\n");
+    for (start, end) in hunks {
+        let (old_start, new_start) =
+            lines
+                .iter()
+                .take(start)
+                .fold((1, 1), |(o, n), line| match line {
+                    DiffLine::Equal(_) => (o + 1, n + 1),
+                    DiffLine::Removed(_) => (o + 1, n),
+                    DiffLine::Added(_) => (o, n + 1),
+                });
+        let hunk = lines.get(start..end).unwrap_or_default();
+        let old_len = hunk
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Added(_)))
+            .count();
+        let new_len = hunk
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Removed(_)))
+            .count();
+
+        out.push_str(&format!(
+            "@@ -{old_start},{old_len} +{new_start},{new_len} @@\n"
+        ));
+        for line in hunk {
+            let (prefix, text) = match line {
+                DiffLine::Equal(text) => (' ', text),
+                DiffLine::Removed(text) => ('-', text),
+                DiffLine::Added(text) => ('+', text),
+            };
+            out.push(prefix);
+            out.push_str(text);
+            if !text.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    /// The length of the longest common subsequence, using the full table.
+    fn lcs_len(old: &[u8], new: &[u8]) -> usize {
+        let mut table = vec![vec![0; new.len() + 1]; old.len() + 1];
+        for (i, a) in old.iter().enumerate() {
+            for (j, b) in new.iter().enumerate() {
+                table[i + 1][j + 1] = if a == b {
+                    table[i][j] + 1
+                } else {
+                    table[i][j + 1].max(table[i + 1][j])
+                };
+            }
+        }
+        table[old.len()][new.len()]
+    }
+
+    #[test]
+    fn lcs_matches_the_full_table() {
+        let cases: [(&[u8], &[u8]); 6] = [
+            (b"", b"abc"),
+            (b"abc", b"abc"),
+            (b"abcbdab", b"bdcaba"),
+            (b"xaaay", b"aaa"),
+            (b"abcdefg", b"gfedcba"),
+            (b"aabbaabbcc", b"abcabcabcc"),
+        ];
+        for (old, new) in cases {
+            let pairs = longest_common_subsequence(old, new);
+            assert_eq!(pairs.len(), lcs_len(old, new), "{old:?} {new:?}");
+            for (i, j) in &pairs {
+                assert_eq!(old[*i], new[*j]);
+            }
+            for pair in pairs.windows(2) {
+                assert!(pair[0].0 < pair[1].0 && pair[0].1 < pair[1].1);
+            }
+        }
+    }
+
+    #[test]
+    fn unified_diff_of_equal_texts_has_no_hunks() {
+        assert_eq!(
+            unified_diff("a\nb\n", "a\nb\n"),
+            "--- expected\n+++ actual\n"
+        );
+    }
+
+    #[test]
+    fn unified_diff_shows_context_around_a_change() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8\n";
+        assert_eq!(
+            unified_diff(old, new),
+            "--- expected\n+++ actual\n@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n"
+        );
+    }
+
+    #[test]
+    fn unified_diff_splits_distant_changes_into_hunks() {
+        let old: String = (0..20).map(|i| format!("{i}\n")).collect();
+        let new = old.replacen("2\n", "two\n", 1).replace("17\n", "");
+        assert_eq!(
+            unified_diff(&old, &new),
+            "--- expected\n+++ actual\n\
+             @@ -1,6 +1,6 @@\n 0\n 1\n-2\n+two\n 3\n 4\n 5\n\
+             @@ -15,6 +15,5 @@\n 14\n 15\n 16\n-17\n 18\n 19\n"
+        );
+    }
+
+    #[test]
+    fn unified_diff_shows_a_missing_newline_at_the_end() {
+        assert_eq!(
+            unified_diff("- window", "- window\n"),
+            "--- expected\n+++ actual\n@@ -1,1 +1,1 @@\n\
+             -- window\n\\ No newline at end of file\n+- window\n"
+        );
+        assert_eq!(
+            unified_diff("a\nb\n", "a\nb"),
+            "--- expected\n+++ actual\n@@ -1,2 +1,2 @@\n a\n-b\n+b\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn unified_diff_of_large_texts() {
+        let old: String = (0..2_000).map(|i| format!("line {i}\n")).collect();
+        let new: String = (0..2_000).map(|i| format!("row {i}\n")).collect();
+        let diff = unified_diff(&old, &new);
+        assert_eq!(
+            diff.lines().filter(|line| line.starts_with('-')).count(),
+            2_001
+        );
+        assert_eq!(
+            diff.lines().filter(|line| line.starts_with('+')).count(),
+            2_001
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use crate::changes::{NodeSummary, diff_properties, node_properties};
+use crate::filter::By;
+use crate::query::query_all;
+use crate::{AccessKitNode, NodeT, TreeNode};
+use accesskit_consumer::TreeState;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+
+/// The last trees of a [`crate::State`], see [`crate::State::with_history`].
+pub(crate) struct History {
+    capacity: usize,
+    frames: VecDeque<(usize, TreeState)>,
+}
+
+impl History {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            frames: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub(crate) fn push(&mut self, frame: usize, tree: TreeState) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.frames.len() >= self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back((frame, tree));
+    }
+
+    pub(crate) fn frames(&self) -> impl DoubleEndedIterator<Item = (usize, AccessKitNode<'_>)> {
+        self.frames
+            .iter()
+            .map(|(frame, tree)| (*frame, tree.root()))
+    }
+
+    pub(crate) fn root_at(&self, frame: usize) -> Option<AccessKitNode<'_>> {
+        self.frames
+            .iter()
+            .find(|(f, _)| *f == frame)
+            .map(|(_, tree)| tree.root())
+    }
+}
+
+/// Describe how the first node matching `by` changed across the recorded frames.
+pub(crate) fn node_history(history: &History, by: &By<'_>) -> String {
+    let mut out = String::new();
+    write_node_history(&mut out, history, by).expect("Writing to a String can't fail");
+    out
+}
+
+fn write_node_history(out: &mut String, history: &History, by: &By<'_>) -> std::fmt::Result {
+    let mut previous: Option<Vec<(&'static str, String)>> = None;
+
+    for (index, (frame, root)) in history.frames().enumerate() {
+        let node = query_all(TreeNode::new(root), by.clone())
+            .next()
+            .map(|node| node.accesskit_node());
+        let properties = node.as_ref().map(node_properties);
+
+        match node.as_ref().zip(properties.as_ref()) {
+            Some((node, properties)) => {
+                let changes = diff_properties(previous.as_deref().unwrap_or_default(), properties);
+                if !changes.is_empty() {
+                    writeln!(out, "frame {frame}: {}", NodeSummary::new(node))?;
+                    for change in changes {
+                        writeln!(out, "  {change}")?;
+                    }
+                }
+            }
+            None if previous.is_some() => writeln!(out, "frame {frame}: removed")?,
+            None if index == 0 => writeln!(out, "frame {frame}: not found")?,
+            None => {}
+        }
+
+        previous = properties;
+    }
+
+    if out.is_empty() {
+        writeln!(out, "No frames recorded")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::State;
+    use crate::filter::by;
+    use crate::test_util::{node, tree_update};
+    use accesskit::{Role, TreeUpdate};
+
+    /// A window with a "Save" button, if `button` is set, with the given value.
+    fn window(button: Option<&str>) -> TreeUpdate {
+        match button {
+            Some(value) => {
+                let mut button = node(Role::Button, Some("Save"), &[]);
+                button.set_value(value);
+                tree_update([(0, node(Role::Window, None, &[1])), (1, button)])
+            }
+            None => tree_update([(0, node(Role::Window, None, &[]))]),
+        }
+    }
+
+    #[test]
+    fn node_history_across_frames() {
+        let mut state = State::new(window(None)).with_history(10);
+        for button in [Some("idle"), Some("idle"), Some("busy"), None] {
+            state.update(window(button));
+        }
+        // Frame 2 is left out, since the button didn't change.
+        assert_eq!(
+            state.node_history(&by().role(Role::Button)),
+            "frame 0: not found\n\
+             frame 1: button \"Save\" (#1)\n  \
+             role: <unset> -> button\n  \
+             label: <unset> -> \"Save\"\n  \
+             value: <unset> -> \"idle\"\n  \
+             children: <unset> -> []\n\
+             frame 3: button \"Save\" (#1)\n  \
+             value: \"idle\" -> \"busy\"\n\
+             frame 4: removed\n"
+        );
+    }
+
+    #[test]
+    fn only_the_last_frames_are_kept() {
+        let mut state = State::new(window(None)).with_history(2);
+        state.update(window(Some("idle")));
+        state.update(window(Some("busy")));
+        assert!(state.root_at(0).is_none());
+        assert_eq!(
+            state.history().map(|(frame, _)| frame).collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert_eq!(
+            state.node_history(&by().role(Role::Button)),
+            "frame 1: button \"Save\" (#1)\n  \
+             role: <unset> -> button\n  \
+             label: <unset> -> \"Save\"\n  \
+             value: <unset> -> \"idle\"\n  \
+             children: <unset> -> []\n\
+             frame 2: button \"Save\" (#1)\n  \
+             value: \"idle\" -> \"busy\"\n"
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use crate::NodeT;
+use std::ops::BitOr;
+
+/// A framework-agnostic key.
+///
+/// Integrations map this to the key type of their ui framework in [`InputSink::key_event`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// A key that produces a character, e.g. `Key::Character('a')`.
+    ///
+    /// Letters should be lowercase, use [`Modifiers::SHIFT`] for uppercase.
+    Character(char),
+    /// The enter / return key.
+    Enter,
+    /// The tab key, which usually moves the focus.
+    Tab,
+    /// The space bar.
+    Space,
+    /// Deletes the character before the cursor.
+    Backspace,
+    /// Deletes the character after the cursor.
+    Delete,
+    /// The escape key, e.g. to close dialogs and popups.
+    Escape,
+    /// The insert key.
+    Insert,
+    /// Moves the cursor to the start of the line.
+    Home,
+    /// Moves the cursor to the end of the line.
+    End,
+    /// Scrolls up by a page.
+    PageUp,
+    /// Scrolls down by a page.
+    PageDown,
+    /// The up arrow key.
+    ArrowUp,
+    /// The down arrow key.
+    ArrowDown,
+    /// The left arrow key.
+    ArrowLeft,
+    /// The right arrow key.
+    ArrowRight,
+    /// A function key, e.g. `Key::F(1)` for F1.
+    F(u8),
+}
+
+/// The modifier keys held down during a [`KeyEvent`].
+///
+/// Modifiers can be combined with `|`, e.g. `Modifiers::CTRL | Modifiers::SHIFT`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    /// The alt key, option on macOS.
+    pub alt: bool,
+    /// The control key.
+    pub ctrl: bool,
+    /// The shift key.
+    pub shift: bool,
+    /// The command key on macOS, the windows key on windows.
+    pub meta: bool,
+}
+
+impl Modifiers {
+    /// No modifiers.
+    pub const NONE: Self = Self {
+        alt: false,
+        ctrl: false,
+        shift: false,
+        meta: false,
+    };
+    /// Only alt.
+    pub const ALT: Self = Self {
+        alt: true,
+        ..Self::NONE
+    };
+    /// Only ctrl.
+    pub const CTRL: Self = Self {
+        ctrl: true,
+        ..Self::NONE
+    };
+    /// Only shift.
+    pub const SHIFT: Self = Self {
+        shift: true,
+        ..Self::NONE
+    };
+    /// Only meta.
+    pub const META: Self = Self {
+        meta: true,
+        ..Self::NONE
+    };
+
+    /// Returns true if no modifier is held down.
+    pub fn is_none(&self) -> bool {
+        *self == Self::NONE
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self {
+            alt: self.alt || rhs.alt,
+            ctrl: self.ctrl || rhs.ctrl,
+            shift: self.shift || rhs.shift,
+            meta: self.meta || rhs.meta,
+        }
+    }
+}
+
+/// A framework-agnostic keyboard event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyEvent {
+    /// A key was pressed.
+    Pressed { key: Key, modifiers: Modifiers },
+
+    /// A key was released.
+    Released { key: Key, modifiers: Modifiers },
+
+    /// Text was entered.
+    ///
+    /// Most frameworks handle text input separately from key presses, so [`type_text`] sends
+    /// both.
+    Text(String),
+}
+
+/// Receives the keyboard events generated by kittest.
+///
+/// Implement this for your harness or node type to map [`KeyEvent`]s to the events of
+/// your ui framework.
+pub trait InputSink {
+    /// Queue a keyboard event, to be handled in the next frame.
+    fn key_event(&self, event: KeyEvent);
+}
+
+/// The key and modifiers [`type_text`] sends for a character, and the text it enters.
+fn char_key(char: char) -> (Key, Modifiers, Option<char>) {
+    match char {
+        '\n' => (Key::Enter, Modifiers::NONE, None),
+        '\t' => (Key::Tab, Modifiers::NONE, None),
+        ' ' => (Key::Space, Modifiers::NONE, Some(char)),
+        _ => {
+            let mut lowercase = char.to_lowercase();
+            match (lowercase.next(), lowercase.next()) {
+                (Some(lower), None) if lower != char => {
+                    (Key::Character(lower), Modifiers::SHIFT, Some(char))
+                }
+                _ => (Key::Character(char), Modifiers::NONE, Some(char)),
+            }
+        }
+    }
+}
+
+/// Type the given text, character by character.
+///
+/// For each character this sends a key press, a [`KeyEvent::Text`] and a key release.
+/// The keys are:
+/// - `'\n'` and `'\t'` are sent as [`Key::Enter`] and [`Key::Tab`] without text
+/// - `' '` is sent as [`Key::Space`]
+/// - uppercase letters (including non-ASCII ones like `'É'`) with a single lowercase letter are
+///   sent as that lowercase [`Key::Character`] with [`Modifiers::SHIFT`]
+/// - everything else, including symbols like `'!'` or `'?'`, is sent as [`Key::Character`] of
+///   the character itself without modifiers, since which keys produce a symbol depends on the
+///   keyboard layout
+pub fn type_text(sink: &(impl InputSink + ?Sized), text: &str) {
+    for char in text.chars() {
+        let (key, modifiers, text) = char_key(char);
+
+        sink.key_event(KeyEvent::Pressed { key, modifiers });
+        if let Some(text) = text {
+            sink.key_event(KeyEvent::Text(text.to_string()));
+        }
+        sink.key_event(KeyEvent::Released { key, modifiers });
+    }
+}
+
+/// Press and release a key while holding the given modifiers, e.g. `Ctrl+A`:
+/// `press_chord(sink, Modifiers::CTRL, Key::Character('a'))`.
+pub fn press_chord(sink: &(impl InputSink + ?Sized), modifiers: Modifiers, key: Key) {
+    sink.key_event(KeyEvent::Pressed { key, modifiers });
+    sink.key_event(KeyEvent::Released { key, modifiers });
+}
+
+/// Keyboard helpers for nodes.
+///
+/// Implement this for your node type (which also needs to implement [`InputSink`]) to get
+/// helpers that focus the node and then send the keys.
+pub trait KeyboardNode<'tree>: NodeT<'tree> + InputSink {
+    /// Focus the node, e.g. by sending an AccessKit [`accesskit::Action::Focus`] request.
+    fn focus(&self);
+
+    /// Focus the node and type the given text. See [`type_text`].
+    fn type_text(&self, text: &str) {
+        self.focus();
+        type_text(self, text);
+    }
+
+    /// Focus the node and press a key with the given modifiers. See [`press_chord`].
+    fn press_chord(&self, modifiers: Modifiers, key: Key) {
+        self.focus();
+        press_chord(self, modifiers, key);
+    }
+
+    /// Focus the node and press a key without modifiers.
+    fn press_key(&self, key: Key) {
+        self.press_chord(Modifiers::NONE, key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct Recorder(RefCell<Vec<KeyEvent>>);
+
+    impl InputSink for Recorder {
+        fn key_event(&self, event: KeyEvent) {
+            self.0.borrow_mut().push(event);
+        }
+    }
+
+    fn pressed(text: &str) -> Vec<(Key, Modifiers)> {
+        let recorder = Recorder::default();
+        type_text(&recorder, text);
+        recorder
+            .0
+            .into_inner()
+            .into_iter()
+            .filter_map(|event| match event {
+                KeyEvent::Pressed { key, modifiers } => Some((key, modifiers)),
+                KeyEvent::Released { .. } | KeyEvent::Text(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn type_text_sends_press_text_and_release() {
+        let recorder = Recorder::default();
+        type_text(&recorder, "a\n");
+        let key = Key::Character('a');
+        assert_eq!(
+            recorder.0.into_inner(),
+            vec![
+                KeyEvent::Pressed {
+                    key,
+                    modifiers: Modifiers::NONE
+                },
+                KeyEvent::Text("a".to_owned()),
+                KeyEvent::Released {
+                    key,
+                    modifiers: Modifiers::NONE
+                },
+                KeyEvent::Pressed {
+                    key: Key::Enter,
+                    modifiers: Modifiers::NONE
+                },
+                KeyEvent::Released {
+                    key: Key::Enter,
+                    modifiers: Modifiers::NONE
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn type_text_uses_shift_for_uppercase_letters() {
+        assert_eq!(
+            pressed("Aé É"),
+            vec![
+                (Key::Character('a'), Modifiers::SHIFT),
+                (Key::Character('é'), Modifiers::NONE),
+                (Key::Space, Modifiers::NONE),
+                (Key::Character('é'), Modifiers::SHIFT),
+            ]
+        );
+    }
+
+    #[test]
+    fn type_text_sends_symbols_without_shift() {
+        assert_eq!(
+            pressed("!@?1"),
+            vec![
+                (Key::Character('!'), Modifiers::NONE),
+                (Key::Character('@'), Modifiers::NONE),
+                (Key::Character('?'), Modifiers::NONE),
+                (Key::Character('1'), Modifiers::NONE),
+            ]
+        );
+    }
+
+    #[test]
+    fn type_text_keeps_letters_without_single_lowercase() {
+        // 'İ' lowercases to two characters.
+        assert_eq!(pressed("İ"), vec![(Key::Character('İ'), Modifiers::NONE)]);
+    }
+}
@@ -1,13 +1,62 @@
 #![allow(clippy::doc_markdown)]
 #![doc = include_str!("../README.md")]
+mod async_runner;
+mod audit;
+mod changes;
+mod contrast;
+mod diff;
 mod filter;
+mod geometry;
+mod golden;
+mod history;
+mod keyboard;
+mod live;
+mod locator;
 mod node;
+mod outline;
+mod pointer;
 mod query;
+mod read_aloud;
+mod redact;
+mod replay;
+mod rules;
+mod runner;
+#[cfg(feature = "serde")]
+mod serialize;
+mod snapshot;
 mod state;
+mod tab_order;
+mod template;
+#[cfg(test)]
+mod test_util;
+mod validate;
 
 /// Re-export of the [`accesskit_consumer::Node`] with a more convenient name.
 pub use accesskit_consumer::Node as AccessKitNode;
+pub use async_runner::*;
+pub use audit::*;
+pub use changes::*;
+pub use contrast::*;
+pub use diff::*;
 pub use filter::*;
+pub use geometry::*;
+pub use golden::*;
+pub use keyboard::*;
+pub use live::*;
+pub use locator::*;
 pub use node::*;
+pub use outline::*;
+pub use pointer::*;
 pub use query::*;
+pub use read_aloud::*;
+pub use redact::*;
+pub use replay::*;
+pub use rules::*;
+pub use runner::*;
+#[cfg(feature = "serde")]
+pub use serialize::*;
+pub use snapshot::*;
 pub use state::*;
+pub use tab_order::*;
+pub use template::*;
+pub use validate::*;
@@ -0,0 +1,190 @@
+use crate::AccessKitNode;
+use crate::node::accessible_name;
+use accesskit::Live;
+use accesskit_consumer::NodeId;
+use std::fmt::{Display, Formatter};
+
+/// Text a screen reader would announce because a live region changed,
+/// see [`crate::State::announcements`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Announcement {
+    /// The frame (see [`crate::State::frame`]) of the update that caused the announcement.
+    pub frame: usize,
+    /// Either [`Live::Polite`] or [`Live::Assertive`].
+    pub politeness: Live,
+    pub text: String,
+}
+
+impl Display for Announcement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let politeness = match self.politeness {
+            Live::Off => "off",
+            Live::Polite => "polite",
+            Live::Assertive => "assertive",
+        };
+        write!(f, "frame {} [{politeness}]: {}", self.frame, self.text)
+    }
+}
+
+/// The text of a single node: its name and value.
+pub(crate) fn node_text(node: &AccessKitNode<'_>) -> Vec<String> {
+    let mut text = Vec::new();
+    text.extend(accessible_name(node));
+    if !node.label_comes_from_value() {
+        text.extend(node.value());
+    }
+    text.retain(|text| !text.trim().is_empty());
+    text
+}
+
+/// The text of a node and its visible descendants.
+pub(crate) fn subtree_text(node: &AccessKitNode<'_>, text: &mut Vec<String>) {
+    if node.is_hidden() {
+        return;
+    }
+    text.extend(node_text(node));
+    for child in node.children() {
+        subtree_text(&child, text);
+    }
+}
+
+/// The node whose text is announced when `node` changes: the closest atomic ancestor within
+/// the live region, or the node itself.
+fn announced_node<'tree>(node: &AccessKitNode<'tree>) -> AccessKitNode<'tree> {
+    let mut current = Some(*node);
+    while let Some(ancestor) = current {
+        if ancestor.is_live_atomic() {
+            return ancestor;
+        }
+        // The live region ends at the node that sets the live property.
+        if ancestor.data().live().is_some() {
+            break;
+        }
+        current = ancestor.parent();
+    }
+    *node
+}
+
+/// Collects the announcements caused by changed nodes in a single update.
+pub(crate) struct AnnouncementCollector {
+    frame: usize,
+    announced: Vec<NodeId>,
+    pub(crate) announcements: Vec<Announcement>,
+}
+
+impl AnnouncementCollector {
+    pub(crate) fn new(frame: usize) -> Self {
+        Self {
+            frame,
+            announced: Vec::new(),
+            announcements: Vec::new(),
+        }
+    }
+
+    /// Announce the node if it is part of a live region.
+    pub(crate) fn node_changed(&mut self, node: &AccessKitNode<'_>) {
+        let politeness = node.live();
+        if politeness == Live::Off || node.is_hidden() {
+            return;
+        }
+
+        let announced = announced_node(node);
+        if self.announced.contains(&announced.id()) {
+            return;
+        }
+        self.announced.push(announced.id());
+
+        let mut text = Vec::new();
+        if announced.id() == node.id() && !announced.is_live_atomic() {
+            text.extend(node_text(node));
+        } else {
+            subtree_text(&announced, &mut text);
+        }
+
+        if !text.is_empty() {
+            self.announcements.push(Announcement {
+                frame: self.frame,
+                politeness,
+                text: text.join(" "),
+            });
+        }
+    }
+
+    /// Announce the node if its text changed and it is part of a live region.
+    pub(crate) fn node_updated(
+        &mut self,
+        old_node: &AccessKitNode<'_>,
+        new_node: &AccessKitNode<'_>,
+    ) {
+        if node_text(old_node) != node_text(new_node)
+            || old_node.is_hidden() != new_node.is_hidden()
+        {
+            self.node_changed(new_node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::State;
+    use crate::test_util::{node, tree_update};
+    use accesskit::{Node, Role, TreeUpdate};
+
+    fn label(text: &str) -> Node {
+        let mut label = node(Role::Label, None, &[]);
+        label.set_value(text);
+        label
+    }
+
+    /// A window with a live region containing a label for each text.
+    fn region(live: Live, atomic: bool, texts: [&str; 2]) -> TreeUpdate {
+        let mut region = node(Role::Group, None, &[2, 3]);
+        region.set_live(live);
+        if atomic {
+            region.set_live_atomic();
+        }
+        tree_update([
+            (0, node(Role::Window, None, &[1])),
+            (1, region),
+            (2, label(texts[0])),
+            (3, label(texts[1])),
+        ])
+    }
+
+    fn announcements(live: Live, atomic: bool) -> Vec<String> {
+        let mut state = State::new(region(live, atomic, ["3", "items"]));
+        state.update(region(live, atomic, ["4", "items"]));
+        state
+            .announcements()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn changed_node_is_announced() {
+        assert_eq!(announcements(Live::Polite, false), ["frame 1 [polite]: 4"]);
+    }
+
+    #[test]
+    fn politeness_comes_from_the_region() {
+        assert_eq!(
+            announcements(Live::Assertive, false),
+            ["frame 1 [assertive]: 4"]
+        );
+    }
+
+    #[test]
+    fn atomic_region_is_announced_as_a_whole() {
+        assert_eq!(
+            announcements(Live::Polite, true),
+            ["frame 1 [polite]: 4 items"]
+        );
+    }
+
+    #[test]
+    fn regions_that_are_off_are_not_announced() {
+        assert!(announcements(Live::Off, false).is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+use crate::filter::By;
+use crate::node::accessible_name;
+use crate::query::{get, query, query_all};
+use crate::{NodeT, Queryable};
+use accesskit::Toggled;
+
+/// A lazy handle to a node, inspired by [Playwright's locators](https://playwright.dev/docs/locators).
+///
+/// Nodes returned by [`Queryable`] borrow the tree, so they have to be queried again after
+/// each frame. A `Locator` only stores the query and resolves it against the current tree
+/// whenever it is used:
+///
+/// ```rust ignore
+/// let checkbox = Locator::new(by().label("Check me!"));
+/// checkbox.assert_toggled(&harness, Toggled::False);
+/// checkbox.get(&harness).click();
+/// harness.run();
+/// checkbox.assert_toggled(&harness, Toggled::True);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Locator<'a> {
+    by: By<'a>,
+    parent: Option<Box<Self>>,
+}
+
+impl<'a> From<By<'a>> for Locator<'a> {
+    fn from(by: By<'a>) -> Self {
+        Self::new(by)
+    }
+}
+
+impl<'a> Locator<'a> {
+    /// Create a locator for the node matching the given [`By`] filter.
+    pub fn new(by: By<'a>) -> Self {
+        Self { by, parent: None }
+    }
+
+    /// Create a locator for a node within the node of this locator.
+    pub fn locator(&self, by: By<'a>) -> Self {
+        Self {
+            by,
+            parent: Some(Box::new(self.clone())),
+        }
+    }
+
+    /// Get the node the query is run on, resolving the parent locators.
+    #[track_caller]
+    fn scope<'tree, Node: NodeT<'tree> + 'tree>(&self, root: Node) -> Node
+    where
+        'a: 'tree,
+    {
+        match &self.parent {
+            Some(parent) => parent.get_from(root),
+            None => root,
+        }
+    }
+
+    #[track_caller]
+    fn get_from<'tree, Node: NodeT<'tree> + 'tree>(&self, root: Node) -> Node
+    where
+        'a: 'tree,
+    {
+        get(self.scope(root), self.by.clone())
+    }
+
+    /// Resolve the locator against the current tree.
+    /// Returns `None` if no node is found.
+    ///
+    /// # Panics
+    /// - if a parent locator doesn't match exactly one node.
+    /// - if more than one node is found matching the query.
+    #[track_caller]
+    pub fn query<'tree, 'node, Node: NodeT<'tree> + 'tree>(
+        &self,
+        root: &'node (impl Queryable<'tree, 'node, Node> + ?Sized),
+    ) -> Option<Node>
+    where
+        'a: 'tree,
+    {
+        query(self.scope(root.queryable_node()), self.by.clone())
+    }
+
+    /// Resolve the locator against the current tree.
+    ///
+    /// # Panics
+    /// - if no nodes are found matching the query.
+    /// - if more than one node is found matching the query.
+    #[track_caller]
+    pub fn get<'tree, 'node, Node: NodeT<'tree> + 'tree>(
+        &self,
+        root: &'node (impl Queryable<'tree, 'node, Node> + ?Sized),
+    ) -> Node
+    where
+        'a: 'tree,
+    {
+        self.get_from(root.queryable_node())
+    }
+
+    /// Resolve the locator against the current tree, returning all matching nodes.
+    ///
+    /// # Panics
+    /// - if a parent locator doesn't match exactly one node.
+    #[track_caller]
+    pub fn query_all<'tree, 'node, Node: NodeT<'tree> + 'tree>(
+        &self,
+        root: &'node (impl Queryable<'tree, 'node, Node> + ?Sized),
+    ) -> Vec<Node>
+    where
+        'a: 'tree,
+    {
+        query_all(self.scope(root.queryable_node()), self.by.clone()).collect()
+    }
+
+    /// Count the nodes matching the locator in the current tree.
+    ///
+    /// # Panics
+    /// - if a parent locator doesn't match exactly one node.
+    #[track_caller]
+    pub fn count<'tree, 'node, Node: NodeT<'tree> + 'tree>(
+        &self,
+        root: &'node (impl Queryable<'tree, 'node, Node> + ?Sized),
+    ) -> usize
+    where
+        'a: 'tree,
+    {
+        query_all(self.scope(root.queryable_node()), self.by.clone()).count()
+    }
+
+    /// Assert that the locator matches a node in the current tree.
+    ///
+    /// # Panics
+    /// - if no node or more than one node matches.
+    #[track_caller]
+    pub fn assert_exists<'tree, 'node, Node: NodeT<'tree> + 'tree>(
+        &self,
+        root: &'node (impl Queryable<'tree, 'node, Node> + ?Sized),
+    ) where
+        'a: 'tree,
+    {
+        self.get(root);
+    }
+
+    /// Assert that the locator doesn't match any node in the current tree.
+    ///
+    /// # Panics
+    /// - if a node matches.
+    #[track_caller]
+    pub fn assert_not_exists<'tree, 'node, Node: NodeT<'tree> + 'tree>(
+        &self,
+        root: &'node (impl Queryable<'tree, 'node, Node> + ?Sized),
+    ) where
+        'a: 'tree,
+    {
+        if let Some(node) = self.query(root) {
+            panic!("Expected no node matching the locator:\n{self:#?}\n\nFound:\n{node:#?}");
+        }
+    }
+
+    /// Assert the toggled state of the node in the current tree.
+    ///
+    /// # Panics
+    /// - if the toggled state doesn't match.
+    /// - if no node or more than one node matches.
+    #[track_caller]
+    pub fn assert_toggled<'tree, 'node, Node: NodeT<'tree> + 'tree>(
+        &self,
+        root: &'node (impl Queryable<'tree, 'node, Node> + ?Sized),
+        toggled: Toggled,
+    ) where
+        'a: 'tree,
+    {
+        let node = self.get(root);
+        assert_eq!(
+            node.accesskit_node().toggled(),
+            Some(toggled),
+            "Unexpected toggled state of node:\n{node:#?}"
+        );
+    }
+
+    /// Assert the label of the node in the current tree.
+    ///
+    /// Like [`By::label`], this uses the value of nodes like `Role::Label`.
+    ///
+    /// # Panics
+    /// - if the label doesn't match.
+    /// - if no node or more than one node matches.
+    #[track_caller]
+    pub fn assert_label<'tree, 'node, Node: NodeT<'tree> + 'tree>(
+        &self,
+        root: &'node (impl Queryable<'tree, 'node, Node> + ?Sized),
+        label: &str,
+    ) where
+        'a: 'tree,
+    {
+        let node = self.get(root);
+        assert_eq!(
+            accessible_name(&node.accesskit_node()).as_deref(),
+            Some(label),
+            "Unexpected label of node:\n{node:#?}"
+        );
+    }
+
+    /// Assert the value of the node in the current tree.
+    ///
+    /// # Panics
+    /// - if the value doesn't match.
+    /// - if no node or more than one node matches.
+    #[track_caller]
+    pub fn assert_value<'tree, 'node, Node: NodeT<'tree> + 'tree>(
+        &self,
+        root: &'node (impl Queryable<'tree, 'node, Node> + ?Sized),
+        value: &str,
+    ) where
+        'a: 'tree,
+    {
+        let node = self.get(root);
+        assert_eq!(
+            node.accesskit_node().value().as_deref(),
+            Some(value),
+            "Unexpected value of node:\n{node:#?}"
+        );
+    }
+
+    /// Assert whether the node in the current tree is disabled.
+    ///
+    /// # Panics
+    /// - if the disabled state doesn't match.
+    /// - if no node or more than one node matches.
+    #[track_caller]
+    pub fn assert_disabled<'tree, 'node, Node: NodeT<'tree> + 'tree>(
+        &self,
+        root: &'node (impl Queryable<'tree, 'node, Node> + ?Sized),
+        disabled: bool,
+    ) where
+        'a: 'tree,
+    {
+        let node = self.get(root);
+        assert_eq!(
+            node.accesskit_node().is_disabled(),
+            disabled,
+            "Unexpected disabled state of node:\n{node:#?}"
+        );
+    }
+
+    /// Assert that the node in the current tree is focused.
+    ///
+    /// # Panics
+    /// - if the node isn't focused.
+    /// - if no node or more than one node matches.
+    #[track_caller]
+    pub fn assert_focused<'tree, 'node, Node: NodeT<'tree> + 'tree>(
+        &self,
+        root: &'node (impl Queryable<'tree, 'node, Node> + ?Sized),
+    ) where
+        'a: 'tree,
+    {
+        let node = self.get(root);
+        assert!(
+            node.accesskit_node().is_focused(),
+            "Expected node to be focused:\n{node:#?}"
+        );
+    }
+}
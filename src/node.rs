@@ -1,7 +1,9 @@
 use crate::AccessKitNode;
 use crate::query::Queryable;
+use crate::redact::Redactions;
 use std::fmt::{Debug, Formatter};
 use std::iter::once;
+use std::marker::PhantomData;
 
 /// A kittest node.
 ///
@@ -62,6 +64,49 @@ impl<'tree, 'node, Node: NodeT<'tree> + 'tree> Queryable<'tree, 'node, Node> for
     }
 }
 
+/// Get the name of a node.
+///
+/// In AccessKit, a widget with `Role::Label` stores its label in `Node::value`, so we
+/// use the value for those.
+pub(crate) fn accessible_name(node: &AccessKitNode<'_>) -> Option<String> {
+    if node.label_comes_from_value() {
+        node.value()
+    } else {
+        node.label()
+    }
+}
+
+/// A [`NodeT`] that only wraps an [`AccessKitNode`].
+///
+/// This allows querying a [`crate::State`] directly, without an integration specific node type.
+#[derive(Clone, Copy)]
+pub struct TreeNode<'tree> {
+    node: AccessKitNode<'tree>,
+}
+
+impl<'tree> TreeNode<'tree> {
+    /// Wrap an [`AccessKitNode`].
+    pub fn new(node: AccessKitNode<'tree>) -> Self {
+        Self { node }
+    }
+}
+
+impl Debug for TreeNode<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        debug_fmt_node(self, f)
+    }
+}
+
+impl<'tree> NodeT<'tree> for TreeNode<'tree> {
+    fn accesskit_node(&self) -> AccessKitNode<'tree> {
+        self.node
+    }
+
+    fn new_related(&self, child_node: AccessKitNode<'tree>) -> Self {
+        Self::new(child_node)
+    }
+}
+
 /// A helper function to nicely format AccessKit nodes.
 ///
 /// # Errors
@@ -69,19 +114,33 @@ impl<'tree, 'node, Node: NodeT<'tree> + 'tree> Queryable<'tree, 'node, Node> for
 pub fn debug_fmt_node<'tree, Node: NodeT<'tree> + 'tree>(
     node: &Node,
     f: &mut Formatter<'_>,
+) -> std::fmt::Result {
+    debug_fmt_node_with(node, f, &Redactions::new())
+}
+
+/// Like [`debug_fmt_node`], but applies the given [`Redactions`] to the node and its children.
+///
+/// # Errors
+/// Returns an error if the formatting fails.
+pub fn debug_fmt_node_with<'tree, Node: NodeT<'tree> + 'tree>(
+    node: &Node,
+    f: &mut Formatter<'_>,
+    redactions: &Redactions<'_>,
 ) -> std::fmt::Result {
     let accesskit_node = node.accesskit_node();
 
     let mut s = f.debug_struct("Node");
-    s.field("id", &accesskit_node.id());
+    if redactions.show_ids() {
+        s.field("id", &accesskit_node.id());
+    }
     s.field("role", &accesskit_node.role());
     if let Some(label) = accesskit_node.label() {
         s.field("label", &label);
     }
-    if let Some(value) = accesskit_node.value() {
+    if let Some(value) = redactions.value(&accesskit_node) {
         s.field("value", &value);
     }
-    if let Some(numeric) = accesskit_node.numeric_value() {
+    if let Some(numeric) = redactions.numeric_value(&accesskit_node) {
         s.field("numeric_value", &numeric);
     }
     s.field("focused", &accesskit_node.is_focused());
@@ -91,9 +150,30 @@ pub fn debug_fmt_node<'tree, Node: NodeT<'tree> + 'tree>(
         s.field("toggled", &toggled);
     }
 
-    let children = node.children().collect::<Vec<_>>();
+    let children = node
+        .children()
+        .filter(|child| !redactions.is_skipped(&child.accesskit_node()))
+        .map(|node| RedactedNode {
+            node,
+            redactions,
+            _tree: PhantomData,
+        })
+        .collect::<Vec<_>>();
 
     s.field("children", &children);
 
     s.finish()
 }
+
+/// Formats a node with [`debug_fmt_node_with`].
+struct RedactedNode<'r, 'tree, Node> {
+    node: Node,
+    redactions: &'r Redactions<'r>,
+    _tree: PhantomData<&'tree ()>,
+}
+
+impl<'tree, Node: NodeT<'tree> + 'tree> Debug for RedactedNode<'_, 'tree, Node> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        debug_fmt_node_with(&self.node, f, self.redactions)
+    }
+}
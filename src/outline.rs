@@ -0,0 +1,358 @@
+use crate::audit::{Rule, Severity, WcagCriterion, is_hidden_in_tree};
+use crate::changes::NodeSummary;
+use crate::live::{node_text, subtree_text};
+use crate::snapshot::role_name;
+use crate::{AccessKitNode, NodeT};
+use accesskit::Role;
+use std::fmt::{Display, Formatter};
+
+/// The heading level assumed for headings without a level, as in ARIA.
+const DEFAULT_HEADING_LEVEL: usize = 2;
+
+/// Returns true for landmark roles, like [`Role::Main`] and [`Role::Navigation`].
+///
+/// Forms and regions are only landmarks if they have a name.
+fn is_landmark(node: &AccessKitNode<'_>) -> bool {
+    match node.role() {
+        Role::Banner
+        | Role::Complementary
+        | Role::ContentInfo
+        | Role::Main
+        | Role::Navigation
+        | Role::Search => true,
+        Role::Form | Role::Region => node.label().is_some(),
+        _ => false,
+    }
+}
+
+/// Returns true if the node or one of its visible descendants is a landmark.
+fn has_landmark(node: &AccessKitNode<'_>) -> bool {
+    !node.is_hidden() && (is_landmark(node) || node.children().any(|child| has_landmark(&child)))
+}
+
+fn heading_level(node: &AccessKitNode<'_>) -> usize {
+    node.level().unwrap_or(DEFAULT_HEADING_LEVEL)
+}
+
+/// A heading or landmark in an [`Outline`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutlineEntry {
+    /// The heading or landmark node. For headings without a name, the name is the text of
+    /// their descendants.
+    pub node: NodeSummary,
+    /// The level of a heading, `None` for landmarks.
+    pub level: Option<usize>,
+    /// Landmarks contain the headings and landmarks within them, headings contain the
+    /// following headings with a higher level.
+    pub children: Vec<Self>,
+}
+
+/// The heading hierarchy and landmark regions of a tree, see [`outline`].
+///
+/// Renders in the format of [`crate::snapshot`]:
+/// ```text
+/// - banner
+///   - heading "My App" [level=1]
+/// - navigation "Sections"
+/// - main
+///   - heading "Settings" [level=2]
+///     - heading "Display" [level=3]
+///     - heading "Sound" [level=3]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Outline {
+    pub entries: Vec<OutlineEntry>,
+}
+
+impl Outline {
+    /// Returns true if there are no headings or landmarks.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn write_entries(
+    f: &mut Formatter<'_>,
+    entries: &[OutlineEntry],
+    depth: usize,
+) -> std::fmt::Result {
+    for entry in entries {
+        write!(f, "{}- {}", "  ".repeat(depth), role_name(entry.node.role))?;
+        if let Some(name) = &entry.node.name {
+            write!(f, " {name:?}")?;
+        }
+        if let Some(level) = entry.level {
+            write!(f, " [level={level}]")?;
+        }
+        writeln!(f)?;
+        write_entries(f, &entry.children, depth + 1)?;
+    }
+    Ok(())
+}
+
+impl Display for Outline {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_entries(f, &self.entries, 0)
+    }
+}
+
+/// Add a heading to `entries`, nested under the last preceding heading with a lower level.
+fn push_heading(entries: &mut Vec<OutlineEntry>, entry: OutlineEntry, level: usize) {
+    if let Some(last) = entries.last_mut()
+        && last.level.is_some_and(|last_level| last_level < level)
+    {
+        push_heading(&mut last.children, entry, level);
+    } else {
+        entries.push(entry);
+    }
+}
+
+fn collect_outline(node: &AccessKitNode<'_>, entries: &mut Vec<OutlineEntry>) {
+    if node.is_hidden() {
+        return;
+    }
+
+    if node.role() == Role::Heading {
+        let mut summary = NodeSummary::new(node);
+        if summary.name.is_none() {
+            let mut text = Vec::new();
+            subtree_text(node, &mut text);
+            summary.name = (!text.is_empty()).then(|| text.join(" "));
+        }
+        let level = heading_level(node);
+        let entry = OutlineEntry {
+            node: summary,
+            level: Some(level),
+            children: Vec::new(),
+        };
+        push_heading(entries, entry, level);
+        // Headings don't contain other headings or landmarks.
+        return;
+    }
+
+    if is_landmark(node) {
+        let mut entry = OutlineEntry {
+            node: NodeSummary::new(node),
+            level: None,
+            children: Vec::new(),
+        };
+        for child in node.children() {
+            collect_outline(&child, &mut entry.children);
+        }
+        entries.push(entry);
+        return;
+    }
+
+    for child in node.children() {
+        collect_outline(&child, entries);
+    }
+}
+
+/// Extract the headings (nodes with [`Role::Heading`] and their [`accesskit::Node::level`])
+/// and landmarks (e.g. [`Role::Main`], [`Role::Navigation`], [`Role::Banner`]) of the node and
+/// its descendants. Hidden nodes are skipped.
+///
+/// ```rust ignore
+/// let outline = outline(&harness.root());
+/// assert_eq!(outline.to_string(), "- heading \"Settings\" [level=1]\n");
+/// ```
+pub fn outline<'tree, Node: NodeT<'tree> + 'tree>(node: &Node) -> Outline {
+    let mut outline = Outline::default();
+    collect_outline(&node.accesskit_node(), &mut outline.entries);
+    outline
+}
+
+/// Call `f` for `node` and its descendants in tree order, skipping hidden subtrees.
+fn visit_visible<'tree>(node: &AccessKitNode<'tree>, f: &mut impl FnMut(&AccessKitNode<'tree>)) {
+    if node.is_hidden() {
+        return;
+    }
+    f(node);
+    for child in node.children() {
+        visit_visible(&child, f);
+    }
+}
+
+/// Heading levels should only increase by one, e.g. a level 2 heading shouldn't be followed by
+/// a level 4 heading.
+pub struct HeadingOrder;
+
+impl Rule for HeadingOrder {
+    fn id(&self) -> &'static str {
+        "heading-order"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn wcag(&self) -> &'static [WcagCriterion] {
+        &[WcagCriterion::INFO_AND_RELATIONSHIPS]
+    }
+
+    fn check_tree<'tree>(
+        &self,
+        root: &AccessKitNode<'tree>,
+    ) -> Vec<(AccessKitNode<'tree>, String)> {
+        let mut problems = Vec::new();
+        if is_hidden_in_tree(root) {
+            return problems;
+        }
+        let mut previous = None;
+        visit_visible(root, &mut |node| {
+            if node.role() != Role::Heading {
+                return;
+            }
+            let level = heading_level(node);
+            if let Some(previous) = previous
+                && level > previous + 1
+            {
+                problems.push((
+                    *node,
+                    format!(
+                        "Heading level {level} skips level {} (the previous heading has level {previous})",
+                        previous + 1
+                    ),
+                ));
+            }
+            previous = Some(level);
+        });
+        problems
+    }
+}
+
+/// There should only be a single [`Role::Main`] landmark.
+pub struct SingleMain;
+
+impl Rule for SingleMain {
+    fn id(&self) -> &'static str {
+        "single-main"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_tree<'tree>(
+        &self,
+        root: &AccessKitNode<'tree>,
+    ) -> Vec<(AccessKitNode<'tree>, String)> {
+        let mut problems = Vec::new();
+        if is_hidden_in_tree(root) {
+            return problems;
+        }
+        let mut first_main = None;
+        visit_visible(root, &mut |node| {
+            if node.role() != Role::Main {
+                return;
+            }
+            if let Some(first) = &first_main {
+                let message = format!("There already is a main landmark: {first}");
+                problems.push((*node, message));
+            } else {
+                first_main = Some(NodeSummary::new(node));
+            }
+        });
+        problems
+    }
+}
+
+/// If a tree uses landmarks, all text should be within a landmark, so users that navigate by
+/// landmarks don't miss it. Trees without landmarks are skipped.
+pub struct ContentOutsideLandmarks;
+
+impl ContentOutsideLandmarks {
+    fn collect<'tree>(
+        node: &AccessKitNode<'tree>,
+        in_landmark: bool,
+        problems: &mut Vec<(AccessKitNode<'tree>, String)>,
+    ) {
+        if node.is_hidden() {
+            return;
+        }
+        let in_landmark = in_landmark || is_landmark(node);
+        if node.children().next().is_none() {
+            if !in_landmark && !node_text(node).is_empty() {
+                problems.push((*node, "Content is outside of all landmarks".to_owned()));
+            }
+            return;
+        }
+        for child in node.children() {
+            Self::collect(&child, in_landmark, problems);
+        }
+    }
+}
+
+impl Rule for ContentOutsideLandmarks {
+    fn id(&self) -> &'static str {
+        "content-outside-landmarks"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_tree<'tree>(
+        &self,
+        root: &AccessKitNode<'tree>,
+    ) -> Vec<(AccessKitNode<'tree>, String)> {
+        let mut problems = Vec::new();
+        let mut tree_root = *root;
+        let mut in_landmark = false;
+        while let Some(parent) = tree_root.parent() {
+            in_landmark |= is_landmark(&parent);
+            tree_root = parent;
+        }
+        if !is_hidden_in_tree(root) && has_landmark(&tree_root) {
+            Self::collect(root, in_landmark, &mut problems);
+        }
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{node, state};
+    use crate::{State, audit_node};
+
+    fn heading(label: &str, level: usize) -> accesskit::Node {
+        let mut heading = node(Role::Heading, Some(label), &[]);
+        heading.set_level(level);
+        heading
+    }
+
+    fn violations(rule: impl Rule + 'static, state: &State) -> Vec<u64> {
+        audit_node(&state.root(), &[Box::new(rule)])
+            .into_iter()
+            .map(|violation| violation.node.id.0)
+            .collect()
+    }
+
+    #[test]
+    fn structure_rules() {
+        let state = state([
+            (0, node(Role::Window, None, &[1, 2, 5, 6])),
+            (1, node(Role::Banner, None, &[])),
+            (2, node(Role::Main, None, &[3, 4])),
+            (3, heading("Title", 1)),
+            (4, heading("Skipped", 3)),
+            (5, node(Role::Main, None, &[])),
+            (6, node(Role::Paragraph, Some("Outside"), &[])),
+        ]);
+        assert_eq!(violations(HeadingOrder, &state), [4]);
+        assert_eq!(violations(SingleMain, &state), [5]);
+        assert_eq!(violations(ContentOutsideLandmarks, &state), [6]);
+    }
+
+    #[test]
+    fn trees_without_landmarks_are_fine() {
+        let state = state([
+            (0, node(Role::Window, None, &[1, 2])),
+            (1, heading("Title", 2)),
+            (2, node(Role::Paragraph, Some("Text"), &[])),
+        ]);
+        assert!(violations(HeadingOrder, &state).is_empty());
+        assert!(violations(ContentOutsideLandmarks, &state).is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+use crate::NodeT;
+use accesskit::{Point, Rect, Vec2};
+
+/// A framework-agnostic pointer button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PointerButton {
+    /// The left mouse button / a touch.
+    Primary,
+    /// The right mouse button.
+    Secondary,
+    /// The middle mouse button.
+    Middle,
+}
+
+/// A framework-agnostic pointer event.
+///
+/// Positions are in the same (global) coordinate space as
+/// [`AccessKitNode::bounding_box`](crate::AccessKitNode::bounding_box).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PointerEvent {
+    /// The pointer moved to the given position.
+    Moved { position: Point },
+
+    /// A button was pressed at the given position.
+    Pressed {
+        position: Point,
+        button: PointerButton,
+    },
+
+    /// A button was released at the given position.
+    Released {
+        position: Point,
+        button: PointerButton,
+    },
+
+    /// The scroll wheel was used while hovering the given position.
+    Scroll { position: Point, delta: Vec2 },
+}
+
+/// Receives the pointer events generated by kittest.
+///
+/// Implement this for your harness or node type to map [`PointerEvent`]s to the events of
+/// your ui framework.
+pub trait PointerSink {
+    /// Queue a pointer event, to be handled in the next frame.
+    fn pointer_event(&self, event: PointerEvent);
+}
+
+/// Get the center of a rect.
+pub fn rect_center(rect: Rect) -> Point {
+    Point::new((rect.x0 + rect.x1) / 2.0, (rect.y0 + rect.y1) / 2.0)
+}
+
+/// Move the pointer to the given position.
+pub fn hover_at(sink: &(impl PointerSink + ?Sized), position: Point) {
+    sink.pointer_event(PointerEvent::Moved { position });
+}
+
+/// Move the pointer to the given position, then press and release the button.
+pub fn click_at(sink: &(impl PointerSink + ?Sized), position: Point, button: PointerButton) {
+    hover_at(sink, position);
+    sink.pointer_event(PointerEvent::Pressed { position, button });
+    sink.pointer_event(PointerEvent::Released { position, button });
+}
+
+/// Move the pointer to the given position, then click the primary button twice.
+pub fn double_click_at(sink: &(impl PointerSink + ?Sized), position: Point) {
+    click_at(sink, position, PointerButton::Primary);
+    sink.pointer_event(PointerEvent::Pressed {
+        position,
+        button: PointerButton::Primary,
+    });
+    sink.pointer_event(PointerEvent::Released {
+        position,
+        button: PointerButton::Primary,
+    });
+}
+
+/// Press the primary button at `from`, move to `to` (via the midpoint, so frameworks with a
+/// drag threshold see a drag) and release it there.
+pub fn drag(sink: &(impl PointerSink + ?Sized), from: Point, to: Point) {
+    let button = PointerButton::Primary;
+    hover_at(sink, from);
+    sink.pointer_event(PointerEvent::Pressed {
+        position: from,
+        button,
+    });
+    hover_at(
+        sink,
+        Point::new((from.x + to.x) / 2.0, (from.y + to.y) / 2.0),
+    );
+    hover_at(sink, to);
+    sink.pointer_event(PointerEvent::Released {
+        position: to,
+        button,
+    });
+}
+
+/// Move the pointer to the given position and scroll by `delta`.
+pub fn scroll_at(sink: &(impl PointerSink + ?Sized), position: Point, delta: Vec2) {
+    hover_at(sink, position);
+    sink.pointer_event(PointerEvent::Scroll { position, delta });
+}
+
+/// Pointer helpers for nodes.
+///
+/// Implement this for your node type (which also needs to implement [`PointerSink`]) to
+/// simulate real pointer input targeting the node's bounds. Unlike sending AccessKit
+/// actions, this goes through your framework's hit-testing.
+pub trait PointerNode<'tree>: NodeT<'tree> + PointerSink {
+    /// The position pointer events for this node are sent to.
+    /// Defaults to the center of the node's bounding box.
+    ///
+    /// # Panics
+    /// - if the node has no bounds.
+    #[track_caller]
+    fn pointer_target(&self) -> Point {
+        let Some(rect) = self.accesskit_node().bounding_box() else {
+            panic!("Can't target node with the pointer, it has no bounds:\n{self:#?}");
+        };
+        rect_center(rect)
+    }
+
+    /// Move the pointer over the node.
+    #[track_caller]
+    fn hover(&self) {
+        hover_at(self, self.pointer_target());
+    }
+
+    /// Click the node with the primary button.
+    #[track_caller]
+    fn click(&self) {
+        click_at(self, self.pointer_target(), PointerButton::Primary);
+    }
+
+    /// Double-click the node with the primary button.
+    #[track_caller]
+    fn double_click(&self) {
+        double_click_at(self, self.pointer_target());
+    }
+
+    /// Click the node with the secondary button.
+    #[track_caller]
+    fn right_click(&self) {
+        click_at(self, self.pointer_target(), PointerButton::Secondary);
+    }
+
+    /// Drag from this node to the `target` node.
+    #[track_caller]
+    fn drag_to(&self, target: &Self) {
+        drag(self, self.pointer_target(), target.pointer_target());
+    }
+
+    /// Scroll by `delta` while hovering the node.
+    #[track_caller]
+    fn scroll(&self, delta: Vec2) {
+        scroll_at(self, self.pointer_target(), delta);
+    }
+}
@@ -5,7 +5,7 @@ use std::iter::FusedIterator;
 
 #[allow(clippy::needless_pass_by_value)]
 #[track_caller]
-fn query_all<'tree, Node: NodeT<'tree> + 'tree>(
+pub(crate) fn query_all<'tree, Node: NodeT<'tree> + 'tree>(
     node: Node,
     by: By<'tree>,
 ) -> impl DoubleEndedIterator<Item = Node> + FusedIterator<Item = Node> + 'tree {
@@ -57,7 +57,7 @@ fn get_all<'tree, Node: NodeT<'tree> + 'tree>(
 
 #[allow(clippy::needless_pass_by_value)]
 #[track_caller]
-fn query<'tree, Node: NodeT<'tree> + 'tree>(node: Node, by: By<'tree>) -> Option<Node> {
+pub(crate) fn query<'tree, Node: NodeT<'tree> + 'tree>(node: Node, by: By<'tree>) -> Option<Node> {
     let debug_query = by.clone();
     let mut iter = query_all(node, by);
     let result = iter.next();
@@ -74,7 +74,7 @@ fn query<'tree, Node: NodeT<'tree> + 'tree>(node: Node, by: By<'tree>) -> Option
 
 #[allow(clippy::needless_pass_by_value)]
 #[track_caller]
-fn get<'tree, Node: NodeT<'tree> + 'tree>(node: Node, by: By<'tree>) -> Node {
+pub(crate) fn get<'tree, Node: NodeT<'tree> + 'tree>(node: Node, by: By<'tree>) -> Node {
     let debug_query = by.clone();
     let option = query(node.clone(), by);
     if let Some(node) = option {
@@ -261,41 +261,3 @@ pub trait Queryable<'tree, 'node, Node: NodeT<'tree> + 'tree> {
 // }
 //
 // impl<'a, 'b, Filter: FnMut(&'b Node<'b>) -> bool, I: IterType<'a>> FusedIterator for QueryAll<'a, 'b, Filter, I> {}
-
-// TODO: I would like to add the find_by_* methods but I'm not sure how I would update the
-// application from here?
-//
-// pub trait Findable<'tree, 'node, 's>: Queryable<'tree, 'node> {
-//     fn run(&mut self);
-//
-//     fn find_timeout(&self) -> std::time::Duration {
-//         std::time::Duration::from_secs(5)
-//     }
-//
-//     fn find_all_by(
-//         &'node mut self,
-//         f: impl Fn(&Node<'_>) -> bool + Copy + 'tree,
-//     ) -> impl IterType<'tree> + 'tree {
-//         let timeout = self.find_timeout();
-//         let step = timeout / 10;
-//
-//         let mut start_time = std::time::Instant::now();
-//
-//         loop {
-//             {
-//                 let node = self.node();
-//                 let iter = node.query_all_by(f);
-//                 let mut peekable = iter.peekable();
-//                 if !peekable.peek().is_none() {
-//                     return peekable;
-//                 }
-//
-//                 if start_time.elapsed() > timeout {
-//                     panic!("Timeout exceeded while waiting for node");
-//                 }
-//             }
-//
-//             std::thread::sleep(step);
-//         }
-//     }
-// }
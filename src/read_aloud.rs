@@ -0,0 +1,337 @@
+use crate::changes::NodeSummary;
+use crate::live::{node_text, subtree_text};
+use crate::node::accessible_name;
+use crate::snapshot::role_name;
+use crate::{AccessKitNode, NodeT};
+use accesskit::{Role, Toggled};
+use std::fmt::{Display, Formatter};
+
+/// What a screen reader announces for a single node, see [`read_aloud`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Utterance {
+    /// The announced node.
+    pub node: NodeSummary,
+    /// The spoken text, e.g. `"Dark mode, check box, checked"`.
+    pub text: String,
+}
+
+impl Display for Utterance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// Nodes that only structure the layout. They aren't announced, but their children are.
+fn is_presentational(role: Role) -> bool {
+    matches!(
+        role,
+        Role::GenericContainer
+            | Role::LayoutTable
+            | Role::LayoutTableRow
+            | Role::LayoutTableCell
+            | Role::Unknown
+    )
+}
+
+/// Nodes whose descendants are their content. They get their name from it if they don't
+/// have one.
+fn name_from_contents(role: Role) -> bool {
+    matches!(
+        role,
+        Role::Button
+            | Role::DefaultButton
+            | Role::Heading
+            | Role::Link
+            | Role::MenuItem
+            | Role::Tab
+            | Role::ListBoxOption
+            | Role::TreeItem
+    )
+}
+
+/// The spoken role, e.g. `"check box"`. `None` for roles that aren't announced.
+fn role_phrase(node: &AccessKitNode<'_>) -> Option<String> {
+    let phrase = match node.role() {
+        Role::Label | Role::Paragraph | Role::Pane | Role::ScrollView | Role::ListItem => {
+            return None;
+        }
+        Role::Group if node.label().is_none() => return None,
+        Role::Heading => {
+            return Some(node.level().map_or_else(
+                || "heading".to_owned(),
+                |level| format!("heading level {level}"),
+            ));
+        }
+        Role::Button | Role::DefaultButton => "button",
+        Role::Group => "grouping",
+        Role::Image => "graphic",
+        Role::TextInput
+        | Role::SearchInput
+        | Role::EmailInput
+        | Role::NumberInput
+        | Role::PhoneNumberInput
+        | Role::UrlInput
+        | Role::DateInput
+        | Role::DateTimeInput
+        | Role::WeekInput
+        | Role::MonthInput
+        | Role::TimeInput => "edit",
+        Role::MultilineTextInput => "edit, multi line",
+        Role::PasswordInput => "password edit",
+        Role::EditableComboBox => "combo box, edit",
+        _ => {
+            // camelCase to words, e.g. `checkBox` to `check box`.
+            let mut phrase = String::new();
+            for c in role_name(node.role()).chars() {
+                if c.is_uppercase() {
+                    phrase.push(' ');
+                }
+                phrase.extend(c.to_lowercase());
+            }
+            return Some(phrase);
+        }
+    };
+    Some(phrase.to_owned())
+}
+
+/// The spoken states, e.g. `["checked", "disabled"]`.
+fn states(node: &AccessKitNode<'_>) -> Vec<&'static str> {
+    let mut states = Vec::new();
+    let is_toggle_button = matches!(node.role(), Role::Button | Role::DefaultButton);
+    match (node.toggled(), is_toggle_button) {
+        (Some(Toggled::True), false) => states.push("checked"),
+        (Some(Toggled::False), false) => states.push("not checked"),
+        (Some(Toggled::Mixed), false) => states.push("half checked"),
+        (Some(Toggled::True), true) => states.push("pressed"),
+        (Some(Toggled::False), true) => states.push("not pressed"),
+        (Some(Toggled::Mixed), true) => states.push("half pressed"),
+        (None, _) => {}
+    }
+    match node.data().is_expanded() {
+        Some(true) => states.push("expanded"),
+        Some(false) => states.push("collapsed"),
+        None => {}
+    }
+    if node.is_selected() == Some(true) {
+        states.push("selected");
+    }
+    if node.is_disabled() {
+        states.push("disabled");
+    }
+    if node.is_required() {
+        states.push("required");
+    }
+    if node.is_text_input() && node.data().is_read_only() {
+        states.push("read only");
+    }
+    states
+}
+
+/// The number of items of a list, e.g. `"with 3 items"`.
+fn item_count(node: &AccessKitNode<'_>) -> Option<String> {
+    if !matches!(node.role(), Role::List | Role::ListBox) {
+        return None;
+    }
+    let items = node
+        .children()
+        .filter(|child| !child.is_hidden())
+        .filter(|child| matches!(child.role(), Role::ListItem | Role::ListBoxOption))
+        .count();
+    let s = if items == 1 { "" } else { "s" };
+    Some(format!("with {items} item{s}"))
+}
+
+/// The spoken value, e.g. the text of a text input or the value of a slider.
+fn value(node: &AccessKitNode<'_>) -> Option<String> {
+    if node.label_comes_from_value() {
+        return None;
+    }
+    node.value()
+        .or_else(|| node.numeric_value().map(|value| value.to_string()))
+        .filter(|value| !value.trim().is_empty())
+}
+
+fn read_node(node: &AccessKitNode<'_>, utterances: &mut Vec<Utterance>) {
+    if node.is_hidden() || node.role() == Role::TextRun {
+        return;
+    }
+
+    let mut name = accessible_name(node).filter(|name| !name.trim().is_empty());
+    // The children of e.g. buttons and headings are their content, and are read as part of them.
+    let read_children = !name_from_contents(node.role());
+    if name.is_none() && !read_children {
+        let mut text = Vec::new();
+        for child in node.children() {
+            subtree_text(&child, &mut text);
+        }
+        name = (!text.is_empty()).then(|| text.join(" "));
+    }
+    if name.is_none() && matches!(node.role(), Role::Label | Role::Paragraph) {
+        // The text of labels and paragraphs can be in their text runs. Other nodes, e.g. text
+        // inputs, read their text as the value instead.
+        let text: Vec<String> = node
+            .children()
+            .filter(|child| child.role() == Role::TextRun && !child.is_hidden())
+            .flat_map(|child| node_text(&child))
+            .collect();
+        name = (!text.is_empty()).then(|| text.concat());
+    }
+
+    if !is_presentational(node.role()) {
+        let parts: Vec<String> = name
+            .into_iter()
+            .chain(role_phrase(node))
+            .chain(item_count(node))
+            .chain(states(node).into_iter().map(ToOwned::to_owned))
+            .chain(value(node))
+            .collect();
+        if !parts.is_empty() {
+            utterances.push(Utterance {
+                node: NodeSummary::new(node),
+                text: parts.join(", "),
+            });
+        }
+    }
+
+    if read_children {
+        for child in node.children() {
+            read_node(&child, utterances);
+        }
+    }
+}
+
+/// What a screen reader in browse mode announces when reading through the node and its
+/// descendants, one utterance per node in reading (tree) order.
+///
+/// Each utterance is made of the name (for labels and paragraphs without one, the text of
+/// their [`Role::TextRun`] children), the role (e.g. `button`, `heading level 2`), the states
+/// (e.g. `checked`, `collapsed`, `disabled`) and the value. Hidden nodes are skipped, and
+/// layout-only nodes like [`Role::GenericContainer`] are skipped but their children are read.
+///
+/// ```rust ignore
+/// let spoken = read_aloud(&harness.root())
+///     .iter()
+///     .map(ToString::to_string)
+///     .collect::<Vec<_>>();
+/// assert_eq!(spoken, [
+///     "Settings, heading level 1",
+///     "Dark mode, check box, checked",
+///     "Volume, slider, 50",
+///     "Save, button, disabled",
+/// ]);
+/// ```
+pub fn read_aloud<'tree, Node: NodeT<'tree> + 'tree>(node: &Node) -> Vec<Utterance> {
+    let mut utterances = Vec::new();
+    read_node(&node.accesskit_node(), &mut utterances);
+    utterances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{node, state};
+    use accesskit::Node;
+
+    /// Read a tree whose node ids are the indices in `nodes`.
+    fn read(nodes: Vec<Node>) -> Vec<String> {
+        let state = state((0..).zip(nodes));
+        read_aloud(&crate::TreeNode::new(state.root()))
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn check_box() {
+        let mut check_box = node(Role::CheckBox, Some("Dark mode"), &[]);
+        check_box.set_toggled(Toggled::True);
+        let mut disabled = node(Role::CheckBox, Some("Sync"), &[]);
+        disabled.set_toggled(Toggled::False);
+        disabled.set_disabled();
+        let spoken = read(vec![node(Role::Window, None, &[1, 2]), check_box, disabled]);
+        assert_eq!(
+            spoken,
+            [
+                "window",
+                "Dark mode, check box, checked",
+                "Sync, check box, not checked, disabled"
+            ]
+        );
+    }
+
+    #[test]
+    fn heading_level() {
+        let mut heading = node(Role::Heading, None, &[2]);
+        heading.set_level(2);
+        let mut label = node(Role::Label, None, &[]);
+        label.set_value("Settings");
+        let spoken = read(vec![
+            node(Role::GenericContainer, None, &[1]),
+            heading,
+            label,
+        ]);
+        // The label is the content of the heading, so it isn't read again.
+        assert_eq!(spoken, ["Settings, heading level 2"]);
+    }
+
+    #[test]
+    fn hidden_subtree() {
+        let mut hidden = node(Role::Group, Some("Advanced"), &[2]);
+        hidden.set_hidden();
+        let spoken = read(vec![
+            node(Role::GenericContainer, None, &[1, 3]),
+            hidden,
+            node(Role::Button, Some("Reset"), &[]),
+            node(Role::Button, Some("Save"), &[]),
+        ]);
+        assert_eq!(spoken, ["Save, button"]);
+    }
+
+    #[test]
+    fn presentational_container() {
+        let spoken = read(vec![
+            node(Role::GenericContainer, Some("Layout"), &[1]),
+            node(Role::LayoutTable, None, &[2]),
+            node(Role::Link, Some("Docs"), &[]),
+        ]);
+        assert_eq!(spoken, ["Docs, link"]);
+    }
+
+    #[test]
+    fn list_item_count() {
+        let mut hidden_item = node(Role::ListItem, Some("Hidden"), &[]);
+        hidden_item.set_hidden();
+        let spoken = read(vec![
+            node(Role::List, Some("Files"), &[1, 2, 3]),
+            node(Role::ListItem, Some("a.txt"), &[]),
+            node(Role::ListItem, Some("b.txt"), &[]),
+            hidden_item,
+        ]);
+        assert_eq!(spoken, ["Files, list, with 2 items", "a.txt", "b.txt"]);
+    }
+
+    #[test]
+    fn text_runs() {
+        let mut first = node(Role::TextRun, None, &[]);
+        first.set_value("Hello ");
+        let mut second = node(Role::TextRun, None, &[]);
+        second.set_value("world");
+        let spoken = read(vec![
+            node(Role::GenericContainer, None, &[1]),
+            node(Role::Paragraph, None, &[2, 3]),
+            first,
+            second,
+        ]);
+        assert_eq!(spoken, ["Hello world"]);
+    }
+
+    #[test]
+    fn text_input_with_text_runs() {
+        let mut input = node(Role::TextInput, None, &[2]);
+        input.set_value("Bob");
+        let mut run = node(Role::TextRun, None, &[]);
+        run.set_value("Bob");
+        let spoken = read(vec![node(Role::Window, None, &[1]), input, run]);
+        assert_eq!(spoken, ["window", "edit, Bob"]);
+    }
+}
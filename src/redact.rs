@@ -0,0 +1,213 @@
+use crate::AccessKitNode;
+use crate::filter::By;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Rules to hide volatile data (like ids, timestamps or layout) from snapshots and debug output.
+///
+/// The default redacts nothing.
+///
+/// ```rust ignore
+/// let redactions = Redactions::new()
+///     .drop_ids()
+///     .round(1)
+///     .replace_value(by().label("Last saved"), "<time>")
+///     .skip_subtree(by().role(Role::Canvas));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Redactions<'a> {
+    drop_ids: bool,
+    decimals: Option<u32>,
+    value_rules: Vec<ValueRule<'a>>,
+    skip: Vec<By<'a>>,
+}
+
+/// The most decimals [`Redactions::round`] rounds to, about the precision of an `f64`.
+const MAX_DECIMALS: u32 = 15;
+
+#[derive(Clone, Debug)]
+enum ValueRule<'a> {
+    By {
+        by: By<'a>,
+        placeholder: &'a str,
+    },
+    #[cfg(feature = "regex")]
+    Regex {
+        regex: regex::Regex,
+        placeholder: &'a str,
+    },
+}
+
+impl Default for Redactions<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Redactions<'a> {
+    /// Create empty redactions that don't change anything.
+    pub fn new() -> Self {
+        Self {
+            drop_ids: false,
+            decimals: None,
+            value_rules: Vec::new(),
+            skip: Vec::new(),
+        }
+    }
+
+    /// Don't include node ids.
+    pub fn drop_ids(mut self) -> Self {
+        self.drop_ids = true;
+        self
+    }
+
+    /// Round numeric values and bounds to the given number of decimals.
+    ///
+    /// At most 15 decimals are used, since more is beyond the precision of an `f64`.
+    pub fn round(mut self, decimals: u32) -> Self {
+        self.decimals = Some(decimals);
+        self
+    }
+
+    /// Replace the value (and numeric value) of nodes matching the [`By`] filter with a
+    /// placeholder.
+    ///
+    /// Note: [`By::recursive`] is ignored, every node is checked individually.
+    pub fn replace_value(mut self, by: By<'a>, placeholder: &'a str) -> Self {
+        self.value_rules.push(ValueRule::By { by, placeholder });
+        self
+    }
+
+    /// Replace all matches of the regex in node values with a placeholder.
+    #[cfg(feature = "regex")]
+    pub fn replace_value_matching(mut self, regex: regex::Regex, placeholder: &'a str) -> Self {
+        self.value_rules
+            .push(ValueRule::Regex { regex, placeholder });
+        self
+    }
+
+    /// Leave out nodes matching the [`By`] filter, including their children.
+    ///
+    /// Note: [`By::recursive`] is ignored, every node is checked individually.
+    pub fn skip_subtree(mut self, by: By<'a>) -> Self {
+        self.skip.push(by);
+        self
+    }
+
+    /// Should ids be included in the output?
+    pub(crate) fn show_ids(&self) -> bool {
+        !self.drop_ids
+    }
+
+    /// Should the node and its children be left out?
+    pub(crate) fn is_skipped(&self, node: &AccessKitNode<'_>) -> bool {
+        self.skip.iter().any(|by| by.matches(node))
+    }
+
+    /// Round a number according to [`Self::round`].
+    pub(crate) fn number(&self, value: f64) -> f64 {
+        match self.decimals {
+            Some(decimals) => {
+                let factor = 10_f64.powi(decimals.min(MAX_DECIMALS) as i32);
+                let scaled = value * factor;
+                if scaled.is_finite() {
+                    let rounded = scaled.round() / factor;
+                    // Small negative numbers round to -0, which would be shown as "-0".
+                    if rounded == 0.0 { 0.0 } else { rounded }
+                } else {
+                    // Values this large have no fractional part left to round.
+                    value
+                }
+            }
+            None => value,
+        }
+    }
+
+    /// The placeholder replacing the whole value of the node, if any.
+    fn value_placeholder(&self, node: &AccessKitNode<'_>) -> Option<&'a str> {
+        self.value_rules.iter().find_map(|rule| match rule {
+            ValueRule::By { by, placeholder } => by.matches(node).then_some(*placeholder),
+            #[cfg(feature = "regex")]
+            ValueRule::Regex { .. } => None,
+        })
+    }
+
+    /// The redacted text value of the node.
+    pub(crate) fn value(&self, node: &AccessKitNode<'_>) -> Option<String> {
+        let value = node.value()?;
+        if let Some(placeholder) = self.value_placeholder(node) {
+            return Some(placeholder.to_owned());
+        }
+
+        #[cfg(feature = "regex")]
+        let value = self
+            .value_rules
+            .iter()
+            .fold(value, |value, rule| match rule {
+                ValueRule::Regex { regex, placeholder } => {
+                    regex.replace_all(&value, *placeholder).into_owned()
+                }
+                ValueRule::By { .. } => value,
+            });
+        Some(value)
+    }
+
+    /// The redacted numeric value of the node.
+    pub(crate) fn numeric_value(&self, node: &AccessKitNode<'_>) -> Option<RedactedNumber<'a>> {
+        let value = node.numeric_value()?;
+        Some(match self.value_placeholder(node) {
+            Some(placeholder) => RedactedNumber::Placeholder(placeholder),
+            None => RedactedNumber::Number(self.number(value)),
+        })
+    }
+}
+
+/// A numeric value that might have been replaced by a placeholder.
+pub(crate) enum RedactedNumber<'a> {
+    Number(f64),
+    Placeholder(&'a str),
+}
+
+impl Display for RedactedNumber<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{number}"),
+            Self::Placeholder(placeholder) => write!(f, "{placeholder}"),
+        }
+    }
+}
+
+impl Debug for RedactedNumber<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_rounds_to_decimals() {
+        let redactions = Redactions::new().round(2);
+        assert_eq!(redactions.number(1.23456), 1.23);
+        assert_eq!(redactions.number(-0.005_1), -0.01);
+        assert_eq!(Redactions::new().number(1.23456), 1.23456);
+    }
+
+    #[test]
+    fn number_clamps_large_decimals() {
+        for decimals in [15, 16, 400, u32::MAX] {
+            let redactions = Redactions::new().round(decimals);
+            assert_eq!(redactions.number(1.5), 1.5, "{decimals} decimals");
+            assert_eq!(redactions.number(-2.25), -2.25, "{decimals} decimals");
+            assert_eq!(redactions.number(1e300), 1e300, "{decimals} decimals");
+        }
+    }
+
+    #[test]
+    fn number_rounds_to_positive_zero() {
+        let redactions = Redactions::new().round(1);
+        assert_eq!(redactions.number(-0.001).to_string(), "0");
+        assert_eq!(redactions.number(-0.0).to_string(), "0");
+    }
+}
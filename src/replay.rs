@@ -0,0 +1,361 @@
+use crate::query::Queryable;
+use crate::{Runner, State, TreeNode, TreeUpdateError};
+use accesskit::TreeUpdate;
+use std::fmt::{Display, Formatter};
+
+/// A [`State`] that keeps every `TreeUpdate` it receives, so the test can be replayed later
+/// without the app, see [`Replay`].
+///
+/// Use it in place of [`State`] in your harness.
+#[derive(Debug)]
+pub struct Recorder {
+    state: State,
+    updates: Vec<TreeUpdate>,
+}
+
+impl Recorder {
+    /// Create a new Recorder from a `TreeUpdate`
+    ///
+    /// # Panics
+    /// - if the update is invalid, see [`Self::try_new`].
+    #[track_caller]
+    pub fn new(update: TreeUpdate) -> Self {
+        match Self::try_new(update) {
+            Ok(recorder) => recorder,
+            Err(err) => panic!("Invalid TreeUpdate: {err}"),
+        }
+    }
+
+    /// Create a new Recorder from a `TreeUpdate`, checking that it is a valid initial update
+    /// first, see [`State::try_new`].
+    ///
+    /// # Errors
+    /// - if the update is invalid, see [`TreeUpdateError`]
+    pub fn try_new(update: TreeUpdate) -> Result<Self, TreeUpdateError> {
+        Ok(Self {
+            state: State::try_new(update.clone())?,
+            updates: vec![update],
+        })
+    }
+
+    /// Update the state with a new `TreeUpdate` and record it (this should be called after
+    /// each frame)
+    ///
+    /// # Panics
+    /// - if the update is invalid, see [`Self::try_update`].
+    #[track_caller]
+    pub fn update(&mut self, update: TreeUpdate) {
+        if let Err(err) = self.try_update(update) {
+            panic!("Invalid TreeUpdate: {err}");
+        }
+    }
+
+    /// Update the state with a new `TreeUpdate` and record it, checking that it can be applied
+    /// first, see [`State::try_update`]. Invalid updates aren't recorded.
+    ///
+    /// # Errors
+    /// - if the update is invalid, see [`TreeUpdateError`]
+    pub fn try_update(&mut self, update: TreeUpdate) -> Result<(), TreeUpdateError> {
+        self.state.try_update(update.clone())?;
+        self.updates.push(update);
+        Ok(())
+    }
+
+    /// The current state.
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// All recorded updates, starting with the one passed to [`Self::new`].
+    pub fn updates(&self) -> &[TreeUpdate] {
+        &self.updates
+    }
+
+    /// Take the recorded updates.
+    pub fn into_updates(self) -> Vec<TreeUpdate> {
+        self.updates
+    }
+
+    /// Create a [`Replay`] of the recorded updates.
+    pub fn replay(&self) -> Replay {
+        match Replay::new(self.updates.clone()) {
+            Ok(replay) => replay,
+            Err(err) => unreachable!("A Recorder only records valid updates: {err}"),
+        }
+    }
+
+    /// Write the recorded updates as JSON Lines, which can be loaded with
+    /// [`Replay::from_update_log`].
+    ///
+    /// # Errors
+    /// - if writing fails
+    /// - if an update can't be read back from JSON, e.g. because it contains a `NaN` number
+    #[cfg(feature = "serde")]
+    pub fn write_update_log(&self, mut writer: impl std::io::Write) -> std::io::Result<()> {
+        for update in &self.updates {
+            crate::serialize::write_update(&mut writer, update)?;
+        }
+        Ok(())
+    }
+}
+
+// This allows querying the recorder directly, e.g. `recorder.get_by_label("Submit")`.
+impl<'tree, 'node> Queryable<'tree, 'node, TreeNode<'tree>> for Recorder
+where
+    'node: 'tree,
+{
+    fn queryable_node(&'node self) -> TreeNode<'tree> {
+        TreeNode::new(self.state.root())
+    }
+}
+
+/// An error creating a [`Replay`].
+#[derive(Debug)]
+pub enum ReplayError {
+    /// There are no updates to replay.
+    Empty,
+
+    /// An update can't be applied to the state of the frame before it.
+    InvalidUpdate {
+        frame: usize,
+        error: TreeUpdateError,
+    },
+
+    /// Reading or parsing the update log failed, see [`Replay::from_update_log`].
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "There are no TreeUpdates to replay"),
+            Self::InvalidUpdate { frame, error } => {
+                write!(f, "The TreeUpdate of frame {frame} is invalid: {error}")
+            }
+            #[cfg(feature = "serde")]
+            Self::Json(err) => write!(f, "Invalid TreeUpdate log: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Empty => None,
+            Self::InvalidUpdate { error, .. } => Some(error),
+            #[cfg(feature = "serde")]
+            Self::Json(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for ReplayError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+/// Replays recorded `TreeUpdate`s frame by frame, see [`Recorder`].
+///
+/// Frame 0 is the state after the first update. Use [`Replay::step`] or [`Replay::seek`] to move
+/// between frames and query the replay like any [`State`]:
+///
+/// ```rust ignore
+/// let mut replay = Replay::from_update_log(BufReader::new(File::open("updates.jsonl")?))?;
+/// replay.seek(12);
+/// replay.get_by_label("Saving…");
+/// ```
+#[derive(Debug)]
+pub struct Replay {
+    updates: Vec<TreeUpdate>,
+    state: State,
+    frame: usize,
+}
+
+impl Replay {
+    /// Create a replay, starting at frame 0.
+    ///
+    /// All updates are applied once to check them, so moving between frames can't fail later.
+    ///
+    /// # Errors
+    /// - if `updates` is empty
+    /// - if an update is invalid, e.g. the log is corrupt, see [`TreeUpdateError`]
+    pub fn new(updates: Vec<TreeUpdate>) -> Result<Self, ReplayError> {
+        let Some((first, rest)) = updates.split_first() else {
+            return Err(ReplayError::Empty);
+        };
+        let invalid = |frame| move |error| ReplayError::InvalidUpdate { frame, error };
+        let mut state = State::try_new(first.clone()).map_err(invalid(0))?;
+        for (frame, update) in (1..).zip(rest) {
+            state.try_update(update.clone()).map_err(invalid(frame))?;
+        }
+        Ok(Self {
+            state: State::try_new(first.clone()).map_err(invalid(0))?,
+            updates,
+            frame: 0,
+        })
+    }
+
+    /// Load a replay from a JSON Lines log, as written by [`State::new_with_update_log`] or
+    /// [`Recorder::write_update_log`].
+    ///
+    /// # Errors
+    /// - if reading or parsing the log fails
+    /// - if the log is empty or contains an invalid update, see [`Self::new`]
+    #[cfg(feature = "serde")]
+    pub fn from_update_log(reader: impl std::io::BufRead) -> Result<Self, ReplayError> {
+        let updates = crate::read_update_log(reader).collect::<Result<Vec<_>, _>>()?;
+        Self::new(updates)
+    }
+
+    /// The number of frames (recorded updates).
+    pub fn frame_count(&self) -> usize {
+        self.updates.len()
+    }
+
+    /// The current frame.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// The state at the current frame.
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// The recorded updates.
+    pub fn updates(&self) -> &[TreeUpdate] {
+        &self.updates
+    }
+
+    /// Apply the next update.
+    /// Returns false if the replay is already at the last frame.
+    pub fn step(&mut self) -> bool {
+        match self.updates.get(self.frame + 1) {
+            Some(update) => {
+                self.state.update(update.clone());
+                self.frame += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move to the given frame.
+    /// Moving backwards replays the updates from the start.
+    ///
+    /// # Panics
+    /// - if the frame is out of range.
+    #[track_caller]
+    pub fn seek(&mut self, frame: usize) {
+        assert!(
+            frame < self.frame_count(),
+            "Frame {frame} is out of range, the replay has {} frames",
+            self.frame_count()
+        );
+        if frame < self.frame {
+            self.state = self.state_at(0);
+            self.frame = 0;
+        }
+        while self.frame < frame {
+            self.step();
+        }
+    }
+
+    /// Create a new state at the given frame, without moving the replay.
+    ///
+    /// # Panics
+    /// - if the frame is out of range.
+    #[track_caller]
+    pub fn state_at(&self, frame: usize) -> State {
+        let Some((first, rest)) = self
+            .updates
+            .get(..=frame)
+            .and_then(<[TreeUpdate]>::split_first)
+        else {
+            panic!(
+                "Frame {frame} is out of range, the replay has {} frames",
+                self.frame_count()
+            )
+        };
+        let mut state = State::new(first.clone());
+        for update in rest {
+            state.update(update.clone());
+        }
+        state
+    }
+}
+
+impl Runner for Replay {
+    fn step(&mut self) {
+        Self::step(self);
+    }
+
+    fn state(&self) -> &State {
+        &self.state
+    }
+}
+
+// This allows querying the replay directly, e.g. `replay.get_by_label("Submit")`.
+impl<'tree, 'node> Queryable<'tree, 'node, TreeNode<'tree>> for Replay
+where
+    'node: 'tree,
+{
+    fn queryable_node(&'node self) -> TreeNode<'tree> {
+        TreeNode::new(self.state.root())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{node, tree_update};
+    use accesskit::{NodeId, Role};
+
+    fn window(children: &[u64]) -> TreeUpdate {
+        let buttons = children
+            .iter()
+            .map(|&id| (id, node(Role::Button, Some("Button"), &[])));
+        tree_update(std::iter::once((0, node(Role::Window, None, children))).chain(buttons))
+    }
+
+    #[test]
+    fn empty_replay() {
+        assert!(matches!(Replay::new(Vec::new()), Err(ReplayError::Empty)));
+    }
+
+    #[test]
+    fn invalid_focus_is_an_error() {
+        let corrupt = TreeUpdate {
+            focus: NodeId(7),
+            ..window(&[1])
+        };
+        let err = Replay::new(vec![window(&[1]), window(&[1, 2]), corrupt]).unwrap_err();
+        assert!(matches!(
+            err,
+            ReplayError::InvalidUpdate {
+                frame: 2,
+                error: TreeUpdateError::MissingFocus { focus: NodeId(7) },
+            }
+        ));
+    }
+
+    #[test]
+    fn seek_replays_from_the_start() {
+        let mut replay = Replay::new(vec![window(&[]), window(&[1]), window(&[1, 2])]).unwrap();
+        replay.seek(2);
+        assert_eq!(replay.state().root().children().count(), 2);
+        replay.seek(1);
+        assert_eq!(replay.frame(), 1);
+        assert_eq!(replay.state().root().children().count(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn empty_update_log_is_an_error() {
+        let err = Replay::from_update_log(b"\n\n".as_slice()).unwrap_err();
+        assert_eq!(err.to_string(), "There are no TreeUpdates to replay");
+    }
+}
@@ -0,0 +1,234 @@
+use crate::AccessKitNode;
+use crate::audit::{Rule, Severity, WcagCriterion, is_hidden_in_tree, visit};
+use crate::changes::NodeSummary;
+use crate::live::subtree_text;
+use crate::node::accessible_name;
+use accesskit::{Action, NodeId, Role, TreeId};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+/// Returns true for roles users interact with, like buttons and text inputs.
+fn is_control_role(role: Role) -> bool {
+    matches!(
+        role,
+        Role::Button
+            | Role::DefaultButton
+            | Role::CheckBox
+            | Role::RadioButton
+            | Role::Switch
+            | Role::ComboBox
+            | Role::EditableComboBox
+            | Role::TextInput
+            | Role::MultilineTextInput
+            | Role::SearchInput
+            | Role::DateInput
+            | Role::DateTimeInput
+            | Role::WeekInput
+            | Role::MonthInput
+            | Role::TimeInput
+            | Role::EmailInput
+            | Role::NumberInput
+            | Role::PasswordInput
+            | Role::PhoneNumberInput
+            | Role::UrlInput
+            | Role::Slider
+            | Role::SpinButton
+            | Role::Link
+            | Role::MenuItem
+            | Role::MenuItemCheckBox
+            | Role::MenuItemRadio
+            | Role::Tab
+            | Role::ListBoxOption
+            | Role::TreeItem
+            | Role::ColorWell
+            | Role::DisclosureTriangle
+    )
+}
+
+/// Returns true for controls and clickable nodes.
+pub(crate) fn is_interactive(node: &AccessKitNode<'_>) -> bool {
+    is_control_role(node.role()) || node.data().supports_action(Action::Click)
+}
+
+fn has_text(text: Option<String>) -> bool {
+    text.is_some_and(|text| !text.trim().is_empty())
+}
+
+/// Interactive nodes (controls and clickable nodes) need an accessible name.
+pub struct MissingName;
+
+impl Rule for MissingName {
+    fn id(&self) -> &'static str {
+        "missing-name"
+    }
+
+    fn wcag(&self) -> &'static [WcagCriterion] {
+        &[WcagCriterion::NAME_ROLE_VALUE]
+    }
+
+    fn check(&self, node: &AccessKitNode<'_>) -> Option<String> {
+        (is_interactive(node) && !is_hidden_in_tree(node) && !has_text(accessible_name(node)))
+            .then(|| "Interactive node has no accessible name".to_owned())
+    }
+}
+
+/// Images need a text alternative, either as label or description.
+pub struct ImageDescription;
+
+impl Rule for ImageDescription {
+    fn id(&self) -> &'static str {
+        "image-description"
+    }
+
+    fn wcag(&self) -> &'static [WcagCriterion] {
+        &[WcagCriterion::NON_TEXT_CONTENT]
+    }
+
+    fn check(&self, node: &AccessKitNode<'_>) -> Option<String> {
+        (node.role() == Role::Image
+            && !is_hidden_in_tree(node)
+            && !has_text(node.label())
+            && !has_text(node.description()))
+        .then(|| "Image has no label or description".to_owned())
+    }
+}
+
+/// A label should only label a single node, otherwise the names are ambiguous.
+pub struct DuplicateLabelledBy;
+
+impl Rule for DuplicateLabelledBy {
+    fn id(&self) -> &'static str {
+        "duplicate-labelled-by"
+    }
+
+    fn wcag(&self) -> &'static [WcagCriterion] {
+        &[WcagCriterion::INFO_AND_RELATIONSHIPS]
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    /// Reports each node whose label already labels an earlier node (in tree order).
+    fn check_tree<'tree>(
+        &self,
+        root: &AccessKitNode<'tree>,
+    ) -> Vec<(AccessKitNode<'tree>, String)> {
+        // The first node labelled by each label.
+        let mut labelled: HashMap<(TreeId, NodeId), AccessKitNode<'tree>> = HashMap::new();
+        let mut problems = Vec::new();
+        visit(root, &mut |node| {
+            let tree_id = node.locate().1;
+            let labelled_by = node.data().labelled_by();
+            let mut message = None;
+            for (index, target) in labelled_by.iter().enumerate() {
+                if labelled_by
+                    .get(..index)
+                    .unwrap_or_default()
+                    .contains(target)
+                {
+                    message.get_or_insert_with(|| {
+                        format!("Node is labelled by #{} more than once", target.0)
+                    });
+                    continue;
+                }
+                match labelled.entry((tree_id, *target)) {
+                    Entry::Occupied(first) => {
+                        message.get_or_insert_with(|| {
+                            format!(
+                                "Label #{} also labels {}",
+                                target.0,
+                                NodeSummary::new(first.get())
+                            )
+                        });
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(*node);
+                    }
+                }
+            }
+            if let Some(message) = message {
+                problems.push((*node, message));
+            }
+        });
+        problems
+    }
+}
+
+/// Hidden nodes (or nodes in hidden subtrees) shouldn't be focusable, since the focus would
+/// move to a node users can't perceive.
+pub struct FocusableHidden;
+
+impl Rule for FocusableHidden {
+    fn id(&self) -> &'static str {
+        "focusable-hidden"
+    }
+
+    fn wcag(&self) -> &'static [WcagCriterion] {
+        &[WcagCriterion::FOCUS_ORDER]
+    }
+
+    fn check(&self, node: &AccessKitNode<'_>) -> Option<String> {
+        (node.data().supports_action(Action::Focus) && is_hidden_in_tree(node))
+            .then(|| "Hidden node is focusable".to_owned())
+    }
+}
+
+/// Headings need text, either their own name or the text of their descendants.
+pub struct EmptyHeading;
+
+impl Rule for EmptyHeading {
+    fn id(&self) -> &'static str {
+        "empty-heading"
+    }
+
+    fn wcag(&self) -> &'static [WcagCriterion] {
+        &[WcagCriterion::HEADINGS_AND_LABELS]
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, node: &AccessKitNode<'_>) -> Option<String> {
+        if node.role() != Role::Heading || is_hidden_in_tree(node) {
+            return None;
+        }
+        let mut text = Vec::new();
+        subtree_text(node, &mut text);
+        text.is_empty().then(|| "Heading has no text".to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit_node;
+    use crate::test_util::{node, state};
+
+    #[test]
+    fn duplicate_labelled_by_reports_the_later_nodes() {
+        let mut label = node(Role::Label, None, &[]);
+        label.set_value("Name");
+        let mut nodes = vec![(0, node(Role::Window, None, &[1, 2, 3, 4])), (1, label)];
+        for id in 2..=4 {
+            let mut input = node(Role::TextInput, None, &[]);
+            input.set_labelled_by(vec![NodeId(1)]);
+            nodes.push((id, input));
+        }
+        let state = state(nodes);
+
+        let violations = audit_node(&state.root(), &[Box::new(DuplicateLabelledBy)]);
+        let reported: Vec<_> = violations
+            .iter()
+            .map(|violation| (violation.node.id, violation.message.as_str()))
+            .collect();
+        assert_eq!(
+            reported,
+            [
+                (NodeId(3), "Label #1 also labels textInput \"Name\" (#2)"),
+                (NodeId(4), "Label #1 also labels textInput \"Name\" (#2)"),
+            ]
+        );
+    }
+}
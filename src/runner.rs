@@ -0,0 +1,432 @@
+use crate::filter::By;
+use crate::query::{Queryable, query_all};
+use crate::{AccessKitNode, NodeT, State, TreeNode};
+use std::fmt::{Debug, Display, Formatter};
+use std::iter::FusedIterator;
+use std::time::Duration;
+use web_time::Instant;
+
+/// Limits for how long to wait for a condition, see [`wait_for`].
+///
+/// Waiting stops as soon as either limit is hit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WaitOptions {
+    pub(crate) max_frames: usize,
+    pub(crate) timeout: Duration,
+    pub(crate) step_interval: Duration,
+}
+
+impl Default for WaitOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WaitOptions {
+    /// Wait for at most 100 frames or 5 seconds.
+    pub fn new() -> Self {
+        Self {
+            max_frames: 100,
+            timeout: Duration::from_secs(5),
+            step_interval: Duration::ZERO,
+        }
+    }
+
+    /// The maximum number of frames to step.
+    /// Default is 100.
+    pub fn max_frames(mut self, max_frames: usize) -> Self {
+        self.max_frames = max_frames;
+        self
+    }
+
+    /// The maximum wall time to wait.
+    /// Default is 5 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// How long to sleep after each frame, e.g. to give background work time to finish.
+    /// Default is zero.
+    ///
+    /// Note that sleeping is not supported on the web.
+    pub fn step_interval(mut self, step_interval: Duration) -> Self {
+        self.step_interval = step_interval;
+        self
+    }
+}
+
+/// The error returned if a condition wasn't met within the [`WaitOptions`] limits.
+#[derive(Clone, Debug)]
+pub struct WaitError<E> {
+    /// The number of frames that were stepped.
+    pub frames: usize,
+    /// The wall time spent waiting.
+    pub elapsed: Duration,
+    /// The error returned by the last check of the condition.
+    pub last_error: E,
+}
+
+impl<E: Display> Display for WaitError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Timed out after {} frames ({:?}): {}",
+            self.frames, self.elapsed, self.last_error
+        )
+    }
+}
+
+impl<E: Debug + Display> std::error::Error for WaitError<E> {}
+
+/// Advances the application under test.
+///
+/// Implement this for your harness to unlock the `find_*` methods of [`Findable`].
+pub trait Runner {
+    /// Run a single frame of the application and update the [`State`] with the new
+    /// `TreeUpdate`.
+    fn step(&mut self);
+
+    /// The current kittest [`State`].
+    fn state(&self) -> &State;
+
+    /// The limits used by the `find_*` methods and [`wait_for`].
+    fn wait_options(&self) -> WaitOptions {
+        WaitOptions::default()
+    }
+}
+
+/// Step the runner until `condition` returns `Ok`, using the runner's [`Runner::wait_options`].
+///
+/// The condition is checked before the first frame is stepped, so this returns immediately if
+/// it is already met.
+///
+/// ```rust ignore
+/// wait_for(&mut harness, |root| {
+///     root.query_by_label("Loading…").map_or(Ok(()), |_| Err("Still loading"))
+/// })?;
+/// ```
+///
+/// # Errors
+/// Returns the last error of the condition if the limits are hit before it returns `Ok`.
+pub fn wait_for<R: Runner + ?Sized, T, E>(
+    runner: &mut R,
+    condition: impl FnMut(TreeNode<'_>) -> Result<T, E>,
+) -> Result<T, WaitError<E>> {
+    let options = runner.wait_options();
+    wait_for_with_options(runner, options, condition)
+}
+
+/// Step the runner until `condition` returns `Ok`, using the given [`WaitOptions`].
+///
+/// See [`wait_for`].
+///
+/// # Errors
+/// Returns the last error of the condition if the limits are hit before it returns `Ok`.
+pub fn wait_for_with_options<R: Runner + ?Sized, T, E>(
+    runner: &mut R,
+    options: WaitOptions,
+    mut condition: impl FnMut(TreeNode<'_>) -> Result<T, E>,
+) -> Result<T, WaitError<E>> {
+    let start = Instant::now();
+    let mut frames = 0;
+
+    loop {
+        let last_error = match condition(TreeNode::new(runner.state().root())) {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+
+        let elapsed = start.elapsed();
+        if frames >= options.max_frames || elapsed >= options.timeout {
+            return Err(WaitError {
+                frames,
+                elapsed,
+                last_error,
+            });
+        }
+
+        runner.step();
+        frames += 1;
+
+        if !options.step_interval.is_zero() {
+            std::thread::sleep(options.step_interval);
+        }
+    }
+}
+
+/// Step the runner until no node matches `by`, using the runner's [`Runner::wait_options`].
+///
+/// Useful to wait for e.g. a spinner or a closing dialog to disappear.
+///
+/// # Errors
+/// Returns an error describing the remaining node if the limits are hit before it is removed.
+pub fn wait_for_removal<R: Runner + ?Sized>(
+    runner: &mut R,
+    by: By<'_>,
+) -> Result<(), WaitError<String>> {
+    let options = runner.wait_options();
+    wait_for_removal_with_options(runner, options, by)
+}
+
+/// Step the runner until no node matches `by`, using the given [`WaitOptions`].
+///
+/// See [`wait_for_removal`].
+///
+/// # Errors
+/// Returns an error describing the remaining node if the limits are hit before it is removed.
+#[allow(clippy::needless_pass_by_value)]
+pub fn wait_for_removal_with_options<R: Runner + ?Sized>(
+    runner: &mut R,
+    options: WaitOptions,
+    by: By<'_>,
+) -> Result<(), WaitError<String>> {
+    wait_for_with_options(runner, options, removal_condition(&by))
+}
+
+/// A condition that is met once a node matching `by` is in the tree.
+pub(crate) fn query_condition<'a>(
+    by: &'a By<'_>,
+) -> impl FnMut(TreeNode<'_>) -> Result<(), String> + 'a {
+    move |root| {
+        if query_all(root, by.clone()).next().is_some() {
+            Ok(())
+        } else {
+            Err(format!(
+                "No nodes found matching the query:\n{by:#?}\n\nLast tree:\n{root:#?}"
+            ))
+        }
+    }
+}
+
+/// A condition that is met once no node matching `by` is in the tree.
+pub(crate) fn removal_condition<'a>(
+    by: &'a By<'_>,
+) -> impl FnMut(TreeNode<'_>) -> Result<(), String> + 'a {
+    move |root| match query_all(root, by.clone()).next() {
+        Some(node) => Err(format!(
+            "Node matching the query was not removed:\n{by:#?}\n\nNode:\n{node:#?}"
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Step the runner until a node matching `by` is in the tree.
+///
+/// # Panics
+/// - if the [`Runner::wait_options`] limits are hit before a node is found.
+#[track_caller]
+fn wait_for_query<R: Runner + ?Sized>(runner: &mut R, by: &By<'_>) {
+    if let Err(err) = wait_for(runner, query_condition(by)) {
+        panic!("{err}");
+    }
+}
+
+macro_rules! impl_find_helper {
+    (
+        $match_doc:literal,
+        $find_all_label:ident,
+        $find_label:ident,
+        $node:ident,
+        ($($args:ident: $arg_ty:ty),*),
+        $by_expr:expr,
+        $(#[$extra_doc:meta])*
+    ) => {
+        /// Step the app until there are nodes in the tree where
+        #[doc = $match_doc]
+        /// Returns at least one node.
+        $(#[$extra_doc])*
+        ///
+        /// # Panics
+        /// - if no nodes are found within the [`WaitOptions`] limits.
+        #[track_caller]
+        fn $find_all_label(
+            &'node mut self, $($args: $arg_ty),*
+        ) -> impl DoubleEndedIterator<Item = $node> + FusedIterator<Item = $node> + 'tree {
+            let by = $by_expr;
+            wait_for_query(self, &by);
+            let this: &'node Self = self;
+            this.get_all(by)
+        }
+
+        /// Step the app until there is a node in the tree where
+        #[doc = $match_doc]
+        $(#[$extra_doc])*
+        ///
+        /// # Panics
+        /// - if no nodes are found within the [`WaitOptions`] limits.
+        /// - if more than one node is found matching the query.
+        #[track_caller]
+        fn $find_label(&'node mut self, $($args: $arg_ty),*) -> $node {
+            let by = $by_expr;
+            wait_for_query(self, &by);
+            let this: &'node Self = self;
+            this.get(by)
+        }
+    };
+}
+
+/// Provides `find_*` methods that wait for nodes to appear, inspired by
+/// <https://testing-library.com/docs/dom-testing-library/api-async#findby-queries>.
+///
+/// This is implemented for everything that implements both [`Runner`] and [`Queryable`].
+pub trait Findable<'tree, 'node, Node: NodeT<'tree> + 'tree>:
+    Runner + Queryable<'tree, 'node, Node>
+{
+    impl_find_helper!(
+        "the node matches the given [`By`] filter.",
+        find_all,
+        find,
+        Node,
+        (by: By<'tree>),
+        by,
+    );
+
+    impl_find_helper!(
+        "the node label exactly matches given label.",
+        find_all_by_label,
+        find_by_label,
+        Node,
+        (label: &'tree str),
+        By::new().label(label),
+        #[doc = ""]
+        #[doc = "If a node is labelled by another node, the label node will not be included in the results."]
+    );
+
+    impl_find_helper!(
+        "the node label contains the given substring.",
+        find_all_by_label_contains,
+        find_by_label_contains,
+        Node,
+        (label: &'tree str),
+        By::new().label_contains(label),
+        #[doc = ""]
+        #[doc = "If a node is labelled by another node, the label node will not be included in the results."]
+    );
+
+    impl_find_helper!(
+        "the node role and label exactly match the given role and label.",
+        find_all_by_role_and_label,
+        find_by_role_and_label,
+        Node,
+        (role: accesskit::Role, label: &'tree str),
+        By::new().role(role).label(label),
+        #[doc = ""]
+        #[doc = "If a node is labelled by another node, the label node will not be included in the results."]
+    );
+
+    impl_find_helper!(
+        "the node role matches the given role.",
+        find_all_by_role,
+        find_by_role,
+        Node,
+        (role: accesskit::Role),
+        By::new().role(role),
+    );
+
+    impl_find_helper!(
+        "the node value exactly matches the given value.",
+        find_all_by_value,
+        find_by_value,
+        Node,
+        (value: &'tree str),
+        By::new().value(value),
+    );
+
+    impl_find_helper!(
+        "the node matches the given predicate.",
+        find_all_by,
+        find_by,
+        Node,
+        (f: impl Fn(&AccessKitNode<'_>) -> bool + 'tree),
+        By::new().predicate(f),
+    );
+}
+
+impl<'tree, 'node, Node: NodeT<'tree> + 'tree, T: Runner + Queryable<'tree, 'node, Node> + ?Sized>
+    Findable<'tree, 'node, Node> for T
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Replay;
+    use crate::test_util::buttons;
+
+    /// Shows the buttons of each frame in turn, then stays at the last frame.
+    fn replay(frames: &[&[&str]]) -> Replay {
+        Replay::new(frames.iter().map(|labels| buttons(labels)).collect()).unwrap()
+    }
+
+    #[test]
+    fn find_waits_for_the_node() {
+        let mut replay = replay(&[&[], &["Loading"], &["Done"]]);
+        assert_eq!(
+            replay.find_by_label("Done").accesskit_node().role(),
+            accesskit::Role::Button
+        );
+        assert_eq!(replay.frame(), 2);
+    }
+
+    #[test]
+    fn find_returns_without_stepping_if_the_node_exists() {
+        let mut replay = replay(&[&["Done", "Done"], &[]]);
+        assert_eq!(replay.find_all_by_label("Done").count(), 2);
+        assert_eq!(replay.frame(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Timed out after 100 frames")]
+    fn find_panics_if_the_node_never_appears() {
+        replay(&[&[], &["Loading"]]).find_by_label("Done");
+    }
+
+    #[test]
+    fn wait_for_returns_the_value_of_the_condition() {
+        let mut replay = replay(&[&[], &["A"], &["A", "B"]]);
+        let count = wait_for(&mut replay, |root| match root.children().count() {
+            2 => Ok(2),
+            count => Err(count),
+        });
+        assert_eq!(count.unwrap(), 2);
+        assert_eq!(replay.frame(), 2);
+    }
+
+    #[test]
+    fn wait_for_stops_at_max_frames() {
+        let mut checks = 0;
+        let options = WaitOptions::new().max_frames(3);
+        let err = wait_for_with_options(&mut replay(&[&[]]), options, |_| {
+            checks += 1;
+            Err::<(), _>(checks)
+        })
+        .unwrap_err();
+        assert_eq!(err.frames, 3);
+        // The condition is checked before the first and after every frame.
+        assert_eq!(err.last_error, 4);
+        assert!(err.to_string().starts_with("Timed out after 3 frames"));
+    }
+
+    #[test]
+    fn wait_for_stops_at_the_timeout() {
+        let mut replay = replay(&[&[], &["Done"]]);
+        let options = WaitOptions::new().timeout(Duration::ZERO);
+        let err =
+            wait_for_with_options(&mut replay, options, |_| Err::<(), _>("never")).unwrap_err();
+        assert_eq!(err.frames, 0);
+        assert_eq!(replay.frame(), 0);
+    }
+
+    #[test]
+    fn wait_for_removal_steps_until_the_node_is_gone() {
+        let mut replay = replay(&[&["Spinner"], &["Spinner"], &["Done"]]);
+        assert!(wait_for_removal(&mut replay, By::new().label("Spinner")).is_ok());
+        assert_eq!(replay.frame(), 2);
+
+        let options = WaitOptions::new().max_frames(1);
+        let err = wait_for_removal_with_options(&mut replay, options, By::new().label("Done"))
+            .unwrap_err();
+        assert!(err.last_error.contains("was not removed"));
+    }
+}
@@ -0,0 +1,441 @@
+use crate::{AccessKitNode, NodeT, State, TreeUpdateError};
+use accesskit::{NodeId, Rect, Role, Toggled, Tree, TreeId, TreeUpdate};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, Write};
+
+/// A node and its children in a stable JSON schema.
+///
+/// Only the properties that kittest cares about are included, so this can be attached to e.g.
+/// CI failures and loaded again later via [`SerializedState::to_tree_update`].
+/// The ids are the local AccessKit node ids.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedNode {
+    /// The local AccessKit node id.
+    pub id: u64,
+    /// The role of the node.
+    pub role: Role,
+    /// The label of the node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// The value, e.g. the text of a text input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// The numeric value, e.g. of a slider.
+    /// `NaN` and infinite values are written as the strings `"NaN"`, `"inf"` and `"-inf"`,
+    /// since JSON numbers can't represent them.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "non_finite::number"
+    )]
+    pub numeric_value: Option<f64>,
+    /// The accessible description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The state of a checkbox or toggle button.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toggled: Option<Toggled>,
+    /// Whether the node is expanded, if it can be expanded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expanded: Option<bool>,
+    /// Whether the node is selected, if it can be selected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected: Option<bool>,
+    /// The hierarchical level, e.g. of a heading or tree item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<usize>,
+    /// Whether the node has the focus.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub focused: bool,
+    /// Whether the node is hidden from assistive technology.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub hidden: bool,
+    /// Whether the node is disabled.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub disabled: bool,
+    /// Whether the value can't be edited.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub read_only: bool,
+    /// Whether a value is required, e.g. in a form.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub required: bool,
+    /// The bounding box in global coordinates. Non-finite coordinates are written like in
+    /// [`Self::numeric_value`].
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "non_finite::rect"
+    )]
+    pub bounds: Option<Rect>,
+    /// The children, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Self>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// Serialize `f64`s as JSON numbers, or as strings if they aren't finite.
+mod non_finite {
+    use accesskit::Rect;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+
+    /// An `f64` that is written as `"NaN"`, `"inf"` or `"-inf"` if it isn't finite.
+    struct Number(f64);
+
+    impl Serialize for Number {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self.0 {
+                value if value.is_nan() => serializer.serialize_str("NaN"),
+                f64::INFINITY => serializer.serialize_str("inf"),
+                f64::NEG_INFINITY => serializer.serialize_str("-inf"),
+                value => serializer.serialize_f64(value),
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Number {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            #[derive(Deserialize)]
+            #[serde(untagged)]
+            enum Repr<'a> {
+                Finite(f64),
+                NonFinite(&'a str),
+            }
+
+            match Repr::deserialize(deserializer)? {
+                Repr::Finite(value) => Ok(Self(value)),
+                Repr::NonFinite("NaN") => Ok(Self(f64::NAN)),
+                Repr::NonFinite("inf") => Ok(Self(f64::INFINITY)),
+                Repr::NonFinite("-inf") => Ok(Self(f64::NEG_INFINITY)),
+                Repr::NonFinite(other) => Err(D::Error::custom(format!(
+                    "expected a number, \"NaN\", \"inf\" or \"-inf\", found {other:?}"
+                ))),
+            }
+        }
+    }
+
+    /// For `Option<f64>` fields.
+    pub mod number {
+        use super::Number;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            value: &Option<f64>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            value.map(Number).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<f64>, D::Error> {
+            Ok(Option::<Number>::deserialize(deserializer)?.map(|number| number.0))
+        }
+    }
+
+    /// For `Option<Rect>` fields, with the same fields as accesskit's own `Rect` serialization.
+    pub mod rect {
+        use super::{Number, Rect};
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        #[derive(Serialize, Deserialize)]
+        struct Repr {
+            x0: Number,
+            y0: Number,
+            x1: Number,
+            y1: Number,
+        }
+
+        pub fn serialize<S: Serializer>(
+            value: &Option<Rect>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            value
+                .map(|rect| Repr {
+                    x0: Number(rect.x0),
+                    y0: Number(rect.y0),
+                    x1: Number(rect.x1),
+                    y1: Number(rect.y1),
+                })
+                .serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Rect>, D::Error> {
+            Ok(Option::<Repr>::deserialize(deserializer)?
+                .map(|repr| Rect::new(repr.x0.0, repr.y0.0, repr.x1.0, repr.y1.0)))
+        }
+    }
+}
+
+impl SerializedNode {
+    /// Serialize the node and its children.
+    pub fn new(node: &AccessKitNode<'_>) -> Self {
+        let data = node.data();
+        Self {
+            id: node.locate().0.0,
+            role: node.role(),
+            label: node.label(),
+            value: node.value(),
+            numeric_value: node.numeric_value(),
+            description: node.description(),
+            toggled: node.toggled(),
+            expanded: data.is_expanded(),
+            selected: node.is_selected(),
+            level: node.level(),
+            focused: node.is_focused(),
+            hidden: node.is_hidden(),
+            disabled: node.is_disabled(),
+            read_only: data.is_read_only(),
+            required: node.is_required(),
+            bounds: node.bounding_box(),
+            children: node.children().map(|child| Self::new(&child)).collect(),
+        }
+    }
+
+    fn push_nodes(&self, nodes: &mut Vec<(NodeId, accesskit::Node)>) {
+        let mut node = accesskit::Node::new(self.role);
+        if let Some(label) = &self.label {
+            node.set_label(label.clone());
+        }
+        if let Some(value) = &self.value {
+            node.set_value(value.clone());
+        }
+        if let Some(numeric_value) = self.numeric_value {
+            node.set_numeric_value(numeric_value);
+        }
+        if let Some(description) = &self.description {
+            node.set_description(description.clone());
+        }
+        if let Some(toggled) = self.toggled {
+            node.set_toggled(toggled);
+        }
+        if let Some(expanded) = self.expanded {
+            node.set_expanded(expanded);
+        }
+        if let Some(selected) = self.selected {
+            node.set_selected(selected);
+        }
+        if let Some(level) = self.level {
+            node.set_level(level);
+        }
+        if self.hidden {
+            node.set_hidden();
+        }
+        if self.disabled {
+            node.set_disabled();
+        }
+        if self.read_only {
+            node.set_read_only();
+        }
+        if self.required {
+            node.set_required();
+        }
+        if let Some(bounds) = self.bounds {
+            node.set_bounds(bounds);
+        }
+        node.set_children(
+            self.children
+                .iter()
+                .map(|child| NodeId(child.id))
+                .collect::<Vec<_>>(),
+        );
+        nodes.push((NodeId(self.id), node));
+
+        for child in &self.children {
+            child.push_nodes(nodes);
+        }
+    }
+}
+
+/// A [`State`] in a stable JSON schema, see [`State::to_json`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedState {
+    /// The id of the focused node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus: Option<u64>,
+    /// The root node and its descendants.
+    pub root: SerializedNode,
+}
+
+impl SerializedState {
+    /// Serialize the tree of the state.
+    pub fn new(state: &State) -> Self {
+        let root = state.root();
+        Self {
+            focus: state.tree_state().focus().map(|node| node.locate().0.0),
+            root: SerializedNode::new(&root),
+        }
+    }
+
+    /// Create a `TreeUpdate` that recreates the serialized tree.
+    ///
+    /// Properties that aren't part of [`SerializedNode`] are lost.
+    pub fn to_tree_update(&self) -> TreeUpdate {
+        let mut nodes = Vec::new();
+        self.root.push_nodes(&mut nodes);
+        let root = NodeId(self.root.id);
+        TreeUpdate {
+            nodes,
+            tree: Some(Tree::new(root)),
+            tree_id: TreeId::ROOT,
+            focus: self.focus.map_or(root, NodeId),
+        }
+    }
+}
+
+/// Serialize a node and its children to pretty printed JSON, see [`SerializedNode`].
+pub fn node_to_json<'tree, Node: NodeT<'tree> + 'tree>(node: &Node) -> String {
+    serde_json::to_string_pretty(&SerializedNode::new(&node.accesskit_node()))
+        .expect("Serializing a node can't fail")
+}
+
+/// Read a JSON Lines log of `TreeUpdate`s, as written by [`State::new_with_update_log`].
+///
+/// Empty lines are skipped.
+pub fn read_update_log(
+    reader: impl BufRead,
+) -> impl Iterator<Item = serde_json::Result<TreeUpdate>> {
+    reader.lines().filter_map(|line| match line {
+        Ok(line) if line.trim().is_empty() => None,
+        Ok(line) => Some(serde_json::from_str(&line)),
+        Err(err) => Some(Err(serde_json::Error::io(err))),
+    })
+}
+
+/// Write a `TreeUpdate` as one line of a JSON Lines log.
+///
+/// serde_json writes non-finite numbers (e.g. a `NaN` numeric value) as `null`, which can't be
+/// read back, so the line is checked before it is written.
+pub(crate) fn write_update(log: &mut dyn Write, update: &TreeUpdate) -> std::io::Result<()> {
+    let line = serde_json::to_string(update)?;
+    if let Err(err) = serde_json::from_str::<TreeUpdate>(&line) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "The TreeUpdate can't be read back from JSON, it might contain a non-finite number: {err}"
+            ),
+        ));
+    }
+    log.write_all(line.as_bytes())?;
+    log.write_all(b"\n")?;
+    log.flush()
+}
+
+/// An error loading a tree from JSON, see [`State::from_json`].
+#[derive(Debug)]
+pub enum LoadError {
+    /// The JSON is invalid.
+    Json(serde_json::Error),
+
+    /// The JSON is valid, but describes an invalid tree, e.g. the focused node is missing.
+    InvalidTree(TreeUpdateError),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "Invalid JSON: {err}"),
+            Self::InvalidTree(err) => write!(f, "Invalid tree: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(err) => Some(err),
+            Self::InvalidTree(err) => Some(err),
+        }
+    }
+}
+
+impl From<serde_json::Error> for LoadError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+impl From<TreeUpdateError> for LoadError {
+    fn from(err: TreeUpdateError) -> Self {
+        Self::InvalidTree(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slider(numeric_value: f64) -> SerializedNode {
+        SerializedNode {
+            id: 0,
+            role: Role::Slider,
+            label: None,
+            value: None,
+            numeric_value: Some(numeric_value),
+            description: None,
+            toggled: None,
+            expanded: None,
+            selected: None,
+            level: None,
+            focused: false,
+            hidden: false,
+            disabled: false,
+            read_only: false,
+            required: false,
+            bounds: None,
+            children: Vec::new(),
+        }
+    }
+
+    fn round_trip(node: &SerializedNode) -> (String, Option<f64>) {
+        let json = serde_json::to_string(node).unwrap();
+        let node: SerializedNode = serde_json::from_str(&json).unwrap();
+        (json, node.numeric_value)
+    }
+
+    #[test]
+    fn numeric_value_round_trips() {
+        let (json, value) = round_trip(&slider(0.5));
+        assert_eq!(json, r#"{"id":0,"role":"slider","numericValue":0.5}"#);
+        assert_eq!(value, Some(0.5));
+
+        let (json, value) = round_trip(&slider(f64::NAN));
+        assert_eq!(json, r#"{"id":0,"role":"slider","numericValue":"NaN"}"#);
+        assert!(value.is_some_and(f64::is_nan));
+
+        assert_eq!(round_trip(&slider(f64::INFINITY)).1, Some(f64::INFINITY));
+        assert_eq!(
+            round_trip(&slider(f64::NEG_INFINITY)).1,
+            Some(f64::NEG_INFINITY)
+        );
+    }
+
+    #[test]
+    fn invalid_numeric_value_is_an_error() {
+        let json = r#"{"id":0,"role":"slider","numericValue":"many"}"#;
+        assert!(serde_json::from_str::<SerializedNode>(json).is_err());
+    }
+
+    #[test]
+    fn non_finite_bounds_round_trip() {
+        let node = SerializedNode {
+            bounds: Some(Rect::new(0.0, f64::NEG_INFINITY, f64::NAN, 2.0)),
+            ..slider(0.5)
+        };
+        let json = serde_json::to_string(&node).unwrap();
+        assert!(json.contains(r#""bounds":{"x0":0.0,"y0":"-inf","x1":"NaN","y1":2.0}"#));
+        let bounds = serde_json::from_str::<SerializedNode>(&json)
+            .unwrap()
+            .bounds
+            .unwrap();
+        assert_eq!((bounds.y0, bounds.y1), (f64::NEG_INFINITY, 2.0));
+        assert!(bounds.x1.is_nan());
+    }
+}
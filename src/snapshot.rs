@@ -0,0 +1,251 @@
+use crate::node::accessible_name;
+use crate::redact::Redactions;
+use crate::{AccessKitNode, NodeT};
+use accesskit::{Role, Toggled};
+use std::fmt::Write as _;
+
+/// Options for [`snapshot_with_options`].
+#[derive(Clone, Debug)]
+pub struct SnapshotOptions<'a> {
+    max_depth: Option<usize>,
+    include_hidden: bool,
+    values: bool,
+    states: bool,
+    descriptions: bool,
+    bounds: bool,
+    ids: bool,
+    redactions: Redactions<'a>,
+}
+
+impl Default for SnapshotOptions<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> SnapshotOptions<'a> {
+    /// The default options: Include values and states of all visible nodes.
+    pub fn new() -> Self {
+        Self {
+            max_depth: None,
+            include_hidden: false,
+            values: true,
+            states: true,
+            descriptions: false,
+            bounds: false,
+            ids: false,
+            redactions: Redactions::new(),
+        }
+    }
+
+    /// Only include nodes up to the given depth below the snapshot root.
+    /// A depth of 0 only includes the root itself.
+    /// Default is unlimited.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Should hidden nodes (and their children) be included?
+    /// Default is false.
+    pub fn include_hidden(mut self, include_hidden: bool) -> Self {
+        self.include_hidden = include_hidden;
+        self
+    }
+
+    /// Should node values be included, e.g. `- textInput "Name": "Bob"`?
+    /// Default is true.
+    pub fn values(mut self, values: bool) -> Self {
+        self.values = values;
+        self
+    }
+
+    /// Should states be included, e.g. `[checked, disabled]`?
+    /// Default is true.
+    pub fn states(mut self, states: bool) -> Self {
+        self.states = states;
+        self
+    }
+
+    /// Should descriptions be included?
+    /// Default is false.
+    pub fn descriptions(mut self, descriptions: bool) -> Self {
+        self.descriptions = descriptions;
+        self
+    }
+
+    /// Should bounding boxes be included?
+    /// Default is false, since they usually change with unrelated layout changes.
+    pub fn bounds(mut self, bounds: bool) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
+    /// Should node ids be included?
+    /// Default is false, since ids are usually not stable between runs.
+    pub fn ids(mut self, ids: bool) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Hide volatile data from the snapshot, see [`Redactions`].
+    /// Default redacts nothing.
+    pub fn redactions(mut self, redactions: Redactions<'a>) -> Self {
+        self.redactions = redactions;
+        self
+    }
+}
+
+/// Create a compact text snapshot of the node and its children, using the default
+/// [`SnapshotOptions`].
+///
+/// The format is inspired by [Playwright's aria snapshots](https://playwright.dev/docs/aria-snapshots)
+/// and is meant to be stored in golden files:
+/// ```text
+/// - window "My App"
+///   - checkBox "Check me!" [checked=false, focused]
+///   - textInput "Name": "Bob"
+///   - button "Submit" [disabled]
+/// ```
+pub fn snapshot<'tree, Node: NodeT<'tree> + 'tree>(node: &Node) -> String {
+    snapshot_with_options(node, &SnapshotOptions::default())
+}
+
+/// Create a compact text snapshot of the node and its children.
+///
+/// See [`snapshot`].
+pub fn snapshot_with_options<'tree, Node: NodeT<'tree> + 'tree>(
+    node: &Node,
+    options: &SnapshotOptions<'_>,
+) -> String {
+    let mut out = String::new();
+    write_node(&mut out, &node.accesskit_node(), options, 0)
+        .expect("Writing to a String can't fail");
+    out
+}
+
+fn write_node(
+    out: &mut String,
+    node: &AccessKitNode<'_>,
+    options: &SnapshotOptions<'_>,
+    depth: usize,
+) -> std::fmt::Result {
+    let redactions = &options.redactions;
+    if (node.is_hidden() && !options.include_hidden) || redactions.is_skipped(node) {
+        return Ok(());
+    }
+
+    let indent = "  ".repeat(depth);
+    write!(out, "{indent}- {}", role_name(node.role()))?;
+
+    let name = if node.label_comes_from_value() {
+        redactions.value(node)
+    } else {
+        accessible_name(node)
+    };
+    if let Some(name) = name {
+        write!(out, " {name:?}")?;
+    }
+
+    if options.values
+        && !node.label_comes_from_value()
+        && let Some(value) = redactions.value(node)
+    {
+        write!(out, ": {value:?}")?;
+    }
+
+    let attributes = attributes(node, options);
+    if !attributes.is_empty() {
+        write!(out, " [{}]", attributes.join(", "))?;
+    }
+
+    writeln!(out)?;
+
+    if options.max_depth.is_none_or(|max_depth| depth < max_depth) {
+        for child in node.children() {
+            write_node(out, &child, options, depth + 1)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The role in camelCase, e.g. `checkBox`.
+pub(crate) fn role_name(role: Role) -> String {
+    let role = format!("{role:?}");
+    let mut chars = role.chars();
+    chars
+        .next()
+        .map(|first| first.to_lowercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+fn attributes(node: &AccessKitNode<'_>, options: &SnapshotOptions<'_>) -> Vec<String> {
+    let redactions = &options.redactions;
+    let mut attributes = Vec::new();
+
+    if options.ids && redactions.show_ids() {
+        attributes.push(format!("id={}", node.locate().0.0));
+    }
+
+    if options.states {
+        match node.toggled() {
+            Some(Toggled::True) => attributes.push("checked".to_owned()),
+            Some(Toggled::False) => attributes.push("checked=false".to_owned()),
+            Some(Toggled::Mixed) => attributes.push("checked=mixed".to_owned()),
+            None => {}
+        }
+        match node.data().is_expanded() {
+            Some(true) => attributes.push("expanded".to_owned()),
+            Some(false) => attributes.push("expanded=false".to_owned()),
+            None => {}
+        }
+        if node.is_selected() == Some(true) {
+            attributes.push("selected".to_owned());
+        }
+        if let Some(level) = node.level() {
+            attributes.push(format!("level={level}"));
+        }
+        if node.is_disabled() {
+            attributes.push("disabled".to_owned());
+        }
+        if node.data().is_read_only() {
+            attributes.push("readonly".to_owned());
+        }
+        if node.is_required() {
+            attributes.push("required".to_owned());
+        }
+        if node.is_focused() {
+            attributes.push("focused".to_owned());
+        }
+        if node.is_hidden() {
+            attributes.push("hidden".to_owned());
+        }
+    }
+
+    if options.values
+        && let Some(numeric_value) = redactions.numeric_value(node)
+    {
+        attributes.push(format!("value={numeric_value}"));
+    }
+
+    if options.descriptions
+        && let Some(description) = node.description()
+    {
+        attributes.push(format!("description={description:?}"));
+    }
+
+    if options.bounds
+        && let Some(rect) = node.bounding_box()
+    {
+        attributes.push(format!(
+            "bounds={},{} {}x{}",
+            redactions.number(rect.x0),
+            redactions.number(rect.y0),
+            redactions.number(rect.width()),
+            redactions.number(rect.height())
+        ));
+    }
+
+    attributes
+}
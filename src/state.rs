@@ -1,4 +1,10 @@
-use crate::AccessKitNode;
+use crate::changes::{ChangeCollector, NodeSummary};
+use crate::filter::By;
+use crate::history::{History, node_history};
+use crate::query::Queryable;
+use crate::tab_order::{assert_tab_order, tab_order};
+use crate::validate::{NodeIndex, validate_update};
+use crate::{AccessKitNode, Announcement, FocusChange, FrameChanges, TreeNode, TreeUpdateError};
 use accesskit::TreeUpdate;
 use std::fmt::{Debug, Formatter};
 
@@ -6,6 +12,16 @@ use std::fmt::{Debug, Formatter};
 /// also use [`accesskit_consumer::Tree`] directly.
 pub struct State {
     tree: accesskit_consumer::Tree,
+    node_index: NodeIndex,
+    frame: usize,
+    last_changes: FrameChanges,
+    focus_history: Vec<FocusChange>,
+    announcements: Vec<Announcement>,
+    history: Option<History>,
+    #[cfg(feature = "serde")]
+    update_log: Option<Box<dyn std::io::Write + Send>>,
+    #[cfg(feature = "serde")]
+    update_log_error: Option<std::io::Error>,
 }
 
 impl Debug for State {
@@ -14,39 +30,457 @@ impl Debug for State {
     }
 }
 
-struct NoOpChangeHandler;
-
-impl accesskit_consumer::TreeChangeHandler for NoOpChangeHandler {
-    fn node_added(&mut self, _node: &AccessKitNode<'_>) {}
+impl State {
+    /// Create a new State from a `TreeUpdate`
+    ///
+    /// # Panics
+    /// - if the update is invalid, see [`Self::try_new`].
+    #[track_caller]
+    pub fn new(update: TreeUpdate) -> Self {
+        match Self::try_new(update) {
+            Ok(state) => state,
+            Err(err) => panic!("Invalid TreeUpdate: {err}"),
+        }
+    }
 
-    fn node_updated(&mut self, _old_node: &AccessKitNode<'_>, _new_node: &AccessKitNode<'_>) {}
+    /// Create a new State from a `TreeUpdate`, checking that it is a valid initial update first.
+    ///
+    /// # Errors
+    /// - if the update doesn't set `tree` or isn't for [`accesskit::TreeId::ROOT`]
+    /// - if the root, a child or the focused node is missing, see [`TreeUpdateError`]
+    pub fn try_new(update: TreeUpdate) -> Result<Self, TreeUpdateError> {
+        validate_update(&update, None)?;
+        let tree = accesskit_consumer::Tree::new(update, true);
+        let focus_history = vec![FocusChange {
+            frame: 0,
+            node: tree.state().focus().as_ref().map(NodeSummary::new),
+        }];
+        Ok(Self {
+            node_index: NodeIndex::new(tree.state()),
+            tree,
+            frame: 0,
+            last_changes: FrameChanges::default(),
+            focus_history,
+            announcements: Vec::new(),
+            history: None,
+            #[cfg(feature = "serde")]
+            update_log: None,
+            #[cfg(feature = "serde")]
+            update_log_error: None,
+        })
+    }
 
-    fn focus_moved(
-        &mut self,
-        _old_node: Option<&AccessKitNode<'_>>,
-        _new_node: Option<&AccessKitNode<'_>>,
-    ) {
+    /// Keep the trees of the last `frames` frames (including the current one), so they can be
+    /// inspected with [`Self::root_at`] and [`Self::node_history`] when a test fails.
+    ///
+    /// This is opt-in, since it keeps a copy of each tree in memory.
+    pub fn with_history(mut self, frames: usize) -> Self {
+        let mut history = History::new(frames);
+        history.push(self.frame, self.tree.state().clone());
+        self.history = Some(history);
+        self
     }
 
-    fn node_removed(&mut self, _node: &AccessKitNode<'_>) {}
-}
+    /// Create a new State from a `TreeUpdate`, and write this and all following updates to
+    /// `log` as JSON Lines.
+    ///
+    /// The log can be read with [`crate::read_update_log`].
+    /// If writing a later update fails, logging stops and the error is returned by
+    /// [`Self::flush_update_log`].
+    ///
+    /// Updates that can't be read back from JSON, e.g. because they contain a `NaN` number,
+    /// are treated like write errors.
+    ///
+    /// # Errors
+    /// Returns an error if writing the first update fails.
+    ///
+    /// # Panics
+    /// - if the update is invalid, see [`Self::try_new`]. Nothing is written in that case.
+    #[cfg(feature = "serde")]
+    #[track_caller]
+    pub fn new_with_update_log(
+        update: TreeUpdate,
+        log: impl std::io::Write + Send + 'static,
+    ) -> std::io::Result<Self> {
+        if let Err(err) = validate_update(&update, None) {
+            panic!("Invalid TreeUpdate: {err}");
+        }
+        let mut log: Box<dyn std::io::Write + Send> = Box::new(log);
+        crate::serialize::write_update(&mut log, &update)?;
+        let mut state = Self::new(update);
+        state.update_log = Some(log);
+        Ok(state)
+    }
 
-impl State {
-    /// Create a new State from a `TreeUpdate`
-    pub fn new(update: TreeUpdate) -> Self {
-        Self {
-            tree: accesskit_consumer::Tree::new(update, true),
+    /// Flush the update log (see [`Self::new_with_update_log`]).
+    ///
+    /// # Errors
+    /// - if writing an update to the log failed, which stopped the logging
+    /// - if flushing the log fails
+    #[cfg(feature = "serde")]
+    pub fn flush_update_log(&mut self) -> std::io::Result<()> {
+        if let Some(err) = self.update_log_error.take() {
+            return Err(err);
+        }
+        match &mut self.update_log {
+            Some(log) => log.flush(),
+            None => Ok(()),
         }
     }
 
     /// Update the state with a new `TreeUpdate` (this should be called after each frame)
+    ///
+    /// # Panics
+    /// - if the update is invalid, see [`Self::try_update`].
+    #[track_caller]
     pub fn update(&mut self, update: accesskit::TreeUpdate) {
-        self.tree
-            .update_and_process_changes(update, &mut NoOpChangeHandler);
+        if let Err(err) = self.try_update(update) {
+            panic!("Invalid TreeUpdate: {err}");
+        }
+    }
+
+    /// Update the state with a new `TreeUpdate`, checking that it can be applied first.
+    /// The state is left unchanged if the update is invalid.
+    ///
+    /// # Errors
+    /// - if a child or the new root is neither in the update nor in the current tree
+    /// - if the focused node isn't in the tree after the update, or a node would have more than
+    ///   one parent, see [`TreeUpdateError`]
+    pub fn try_update(&mut self, update: accesskit::TreeUpdate) -> Result<(), TreeUpdateError> {
+        validate_update(&update, Some((self.tree.state(), &self.node_index)))?;
+        #[cfg(feature = "serde")]
+        if let Some(log) = &mut self.update_log
+            && let Err(err) = crate::serialize::write_update(log, &update)
+        {
+            // Stop logging, since the log can't be replayed without this update.
+            self.update_log = None;
+            self.update_log_error = Some(err);
+        }
+        self.frame += 1;
+        let mut collector = ChangeCollector::new(self.frame, std::mem::take(&mut self.node_index));
+        self.tree.update_and_process_changes(update, &mut collector);
+        self.node_index = collector.node_index;
+        self.announcements
+            .extend(collector.announcements.announcements);
+        if let Some(focus) = &collector.changes.focus {
+            self.focus_history.push(FocusChange {
+                frame: self.frame,
+                node: focus.to.clone(),
+            });
+        }
+        self.last_changes = collector.changes;
+        if let Some(history) = &mut self.history {
+            history.push(self.frame, self.tree.state().clone());
+        }
+        Ok(())
+    }
+
+    /// The root of the tree at a past frame.
+    /// Returns `None` if the frame isn't in the history (see [`Self::with_history`]).
+    pub fn root_at(&self, frame: usize) -> Option<AccessKitNode<'_>> {
+        self.history.as_ref()?.root_at(frame)
+    }
+
+    /// The recorded frames and their roots, oldest first (see [`Self::with_history`]).
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = (usize, AccessKitNode<'_>)> {
+        self.history.iter().flat_map(History::frames)
+    }
+
+    /// Describe how the first node matching `by` changed across the recorded frames
+    /// (see [`Self::with_history`]). Frames where the node didn't change are left out.
+    ///
+    /// ```text
+    /// frame 3: button "Save" (#4)
+    ///   role: <unset> -> button
+    ///   label: <unset> -> "Save"
+    /// frame 7: button "Saving…" (#4)
+    ///   label: "Save" -> "Saving…"
+    ///   disabled: <unset> -> true
+    /// frame 9: removed
+    /// ```
+    pub fn node_history(&self, by: &By<'_>) -> String {
+        match &self.history {
+            Some(history) => node_history(history, by),
+            None => "No history recorded, see `State::with_history`\n".to_owned(),
+        }
+    }
+
+    /// The number of updates since the state was created.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// The currently focused node.
+    pub fn focused(&self) -> Option<AccessKitNode<'_>> {
+        self.tree_state().focus()
+    }
+
+    /// The focused node after creating the state (or the last [`Self::clear_focus_history`]),
+    /// followed by every focus change.
+    pub fn focus_history(&self) -> &[FocusChange] {
+        &self.focus_history
+    }
+
+    /// Forget the focus changes so far, so [`Self::assert_focus_moved_to`] only considers the
+    /// changes after this point.
+    pub fn clear_focus_history(&mut self) {
+        self.focus_history = vec![FocusChange {
+            frame: self.frame,
+            node: self.focused().as_ref().map(NodeSummary::new),
+        }];
+    }
+
+    /// Assert that the focus moved since the state was created (or the last
+    /// [`Self::clear_focus_history`]) and that the node it moved to matches the [`By`] filter and
+    /// is still focused.
+    ///
+    /// ```rust ignore
+    /// state.clear_focus_history();
+    /// // Click "Close" and update the state with the next frames.
+    /// state.assert_focus_moved_to(by().label("Open dialog"));
+    /// ```
+    ///
+    /// Note: [`By::recursive`] is ignored.
+    ///
+    /// # Panics
+    /// - if the focus didn't move, or the focused node doesn't match.
+    #[track_caller]
+    pub fn assert_focus_moved_to(&self, by: By<'_>) {
+        let start = self.focus_history.first().map_or(0, |change| change.frame);
+        self.assert_focus_moved_since(start, by);
+    }
+
+    /// Assert that the focus moved after the given frame (see [`Self::frame`]) and that the
+    /// node it moved to matches the [`By`] filter and is still focused.
+    ///
+    /// ```rust ignore
+    /// let frame = harness.state().frame();
+    /// harness.get_by_label("Close").click();
+    /// harness.run();
+    /// harness.state().assert_focus_moved_since(frame, by().label("Open dialog"));
+    /// ```
+    ///
+    /// Note: [`By::recursive`] is ignored.
+    ///
+    /// # Panics
+    /// - if the focus didn't move after `frame`, or the focused node doesn't match.
+    #[track_caller]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn assert_focus_moved_since(&self, frame: usize, by: By<'_>) {
+        let history = self
+            .focus_history
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let moved = self
+            .focus_history
+            .last()
+            .is_some_and(|change| change.frame > frame);
+        assert!(
+            moved,
+            "Expected the focus to move to a node matching:\n{by:#?}\n\nBut the focus didn't move after frame {frame}. Focus history:\n{history}"
+        );
+        let focused = self.focused();
+        assert!(
+            focused.as_ref().is_some_and(|node| by.matches(node)),
+            "Expected the focus to move to a node matching:\n{by:#?}\n\nFocused node:\n{:#?}\n\nFocus history:\n{history}",
+            focused.map(TreeNode::new)
+        );
+    }
+
+    /// The focusable nodes in the order the Tab key visits them, see [`crate::tab_order`].
+    pub fn tab_order(&self) -> Vec<AccessKitNode<'_>> {
+        tab_order(&self.root())
+    }
+
+    /// Assert that the Tab key visits exactly the nodes matching the filters, in this order.
+    ///
+    /// ```rust ignore
+    /// harness.state().assert_tab_order(&[
+    ///     by().label("Name"),
+    ///     by().label("Email"),
+    ///     by().role(Role::Button).label("Submit"),
+    /// ]);
+    /// ```
+    ///
+    /// Note: [`By::recursive`] is ignored.
+    ///
+    /// # Panics
+    /// - if the tab order doesn't match.
+    #[track_caller]
+    pub fn assert_tab_order(&self, expected: &[By<'_>]) {
+        assert_tab_order(&self.root(), expected);
+    }
+
+    /// Everything announced by live regions (nodes with [`accesskit::Live::Polite`] or
+    /// [`accesskit::Live::Assertive`], including their descendants) since the state was created.
+    ///
+    /// Added nodes and nodes whose name or value changed are announced. If the change is
+    /// within an atomic region, the text of the whole region is announced.
+    ///
+    /// ```rust ignore
+    /// harness.get_by_label("Save").click();
+    /// harness.run();
+    /// assert!(harness.state().announcements().iter().any(|a| a.text == "File saved"));
+    /// ```
+    pub fn announcements(&self) -> &[Announcement] {
+        &self.announcements
+    }
+
+    /// The announcements of the last [`Self::update`], see [`Self::announcements`].
+    pub fn last_announcements(&self) -> &[Announcement] {
+        let start = self
+            .announcements
+            .iter()
+            .rposition(|announcement| announcement.frame != self.frame)
+            .map_or(0, |index| index + 1);
+        self.announcements.get(start..).unwrap_or_default()
+    }
+
+    /// What changed in the last [`Self::update`].
+    /// Empty for a newly created state.
+    pub fn last_changes(&self) -> &FrameChanges {
+        &self.last_changes
     }
 
     /// Get the root accesskit node
     pub fn root(&self) -> AccessKitNode<'_> {
         self.tree.state().root()
     }
+
+    pub(crate) fn tree_state(&self) -> &accesskit_consumer::TreeState {
+        self.tree.state()
+    }
+
+    /// Serialize the tree to pretty printed JSON, see [`crate::SerializedState`].
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&crate::SerializedState::new(self))
+            .expect("Serializing a tree can't fail")
+    }
+
+    /// Load a tree that was serialized with [`Self::to_json`].
+    ///
+    /// # Errors
+    /// - if the JSON is invalid
+    /// - if it describes an invalid tree, e.g. the focused node is missing
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, crate::LoadError> {
+        let state: crate::SerializedState = serde_json::from_str(json)?;
+        Ok(Self::try_new(state.to_tree_update())?)
+    }
+}
+
+// This allows querying the state directly, e.g. `state.get_by_label("Submit")`.
+impl<'tree, 'node> Queryable<'tree, 'node, TreeNode<'tree>> for State
+where
+    'node: 'tree,
+{
+    fn queryable_node(&'node self) -> TreeNode<'tree> {
+        TreeNode::new(self.root())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::by;
+    use crate::test_util::{node, tree_update};
+    use accesskit::{NodeId, Role};
+
+    /// A window with the buttons "A" and "B", focusing the node with the given id.
+    fn buttons(focus: u64) -> TreeUpdate {
+        TreeUpdate {
+            focus: NodeId(focus),
+            ..tree_update([
+                (0, node(Role::Window, None, &[1, 2])),
+                (1, node(Role::Button, Some("A"), &[])),
+                (2, node(Role::Button, Some("B"), &[])),
+            ])
+        }
+    }
+
+    #[test]
+    fn focus_moved() {
+        let mut state = State::new(buttons(0));
+        state.update(buttons(1));
+        state.assert_focus_moved_to(by().label("A"));
+        state.assert_focus_moved_since(0, by().label("A"));
+    }
+
+    #[test]
+    #[should_panic(expected = "But the focus didn't move after frame 1")]
+    fn stale_focus_move_since_frame() {
+        let mut state = State::new(buttons(0));
+        state.update(buttons(1));
+        let frame = state.frame();
+        state.update(buttons(1));
+        state.assert_focus_moved_since(frame, by().label("A"));
+    }
+
+    #[test]
+    #[should_panic(expected = "But the focus didn't move after frame 1")]
+    fn stale_focus_move_after_clear() {
+        let mut state = State::new(buttons(0));
+        state.update(buttons(1));
+        state.clear_focus_history();
+        state.update(buttons(1));
+        state.assert_focus_moved_to(by().label("A"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn update_log_errors_are_returned_from_flush() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        struct Log(Arc<AtomicBool>);
+
+        impl std::io::Write for Log {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                if self.0.load(Ordering::Relaxed) {
+                    Err(std::io::Error::other("disk full"))
+                } else {
+                    Ok(buf.len())
+                }
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let failing = Arc::new(AtomicBool::new(false));
+        let mut state = State::new_with_update_log(buttons(0), Log(failing.clone())).unwrap();
+        state.update(buttons(1));
+        assert!(state.flush_update_log().is_ok());
+
+        failing.store(true, Ordering::Relaxed);
+        state.update(buttons(2));
+        assert_eq!(state.frame(), 2);
+        let err = state.flush_update_log().unwrap_err();
+        assert_eq!(err.to_string(), "disk full");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn non_finite_numbers_are_rejected_by_the_update_log() {
+        let mut slider = node(Role::Slider, None, &[]);
+        slider.set_numeric_value(f64::NAN);
+        let update = tree_update([(0, node(Role::Window, None, &[1])), (1, slider)]);
+        let err = State::new_with_update_log(update, Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn from_json_with_invalid_focus_is_an_error() {
+        let err =
+            State::from_json(r#"{"focus": 7, "root": {"id": 0, "role": "window"}}"#).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::LoadError::InvalidTree(TreeUpdateError::MissingFocus { focus: NodeId(7) })
+        ));
+    }
 }
@@ -0,0 +1,182 @@
+use crate::AccessKitNode;
+use crate::audit::{Rule, WcagCriterion, is_hidden_in_tree};
+use crate::changes::NodeSummary;
+use crate::filter::By;
+use accesskit::Action;
+use std::fmt::Write as _;
+
+fn push_tabbable<'tree>(node: &AccessKitNode<'tree>, order: &mut Vec<AccessKitNode<'tree>>) {
+    if node.is_hidden() {
+        return;
+    }
+    if node.data().supports_action(Action::Focus) && !node.is_disabled() {
+        order.push(*node);
+    }
+    for child in node.children() {
+        push_tabbable(&child, order);
+    }
+}
+
+/// The nodes the Tab key visits, in order: the focusable (supporting [`Action::Focus`]) nodes
+/// of the tree, in tree order. Hidden and disabled nodes are skipped.
+pub fn tab_order<'tree>(root: &AccessKitNode<'tree>) -> Vec<AccessKitNode<'tree>> {
+    let mut order = Vec::new();
+    push_tabbable(root, &mut order);
+    order
+}
+
+/// Assert that the Tab key visits exactly the nodes matching the filters, in this order.
+/// See [`crate::State::assert_tab_order`].
+///
+/// # Panics
+/// - if the tab order doesn't match.
+#[track_caller]
+pub fn assert_tab_order(root: &AccessKitNode<'_>, expected: &[By<'_>]) {
+    let order = tab_order(root);
+    let matches = order.len() == expected.len()
+        && order
+            .iter()
+            .zip(expected)
+            .all(|(node, by)| by.matches(node));
+    assert!(matches, "{}", tab_order_mismatch(&order, expected));
+}
+
+fn tab_order_mismatch(order: &[AccessKitNode<'_>], expected: &[By<'_>]) -> String {
+    let mut out = String::new();
+    write_tab_order_mismatch(&mut out, order, expected).expect("Writing to a String can't fail");
+    out
+}
+
+fn write_tab_order_mismatch(
+    out: &mut String,
+    order: &[AccessKitNode<'_>],
+    expected: &[By<'_>],
+) -> std::fmt::Result {
+    writeln!(out, "Tab order doesn't match.")?;
+    for index in 0..order.len().max(expected.len()) {
+        let node = order.get(index);
+        let by = expected.get(index);
+        let ok = node.zip(by).is_some_and(|(node, by)| by.matches(node));
+        let actual = node.map_or_else(
+            || "<nothing>".to_owned(),
+            |node| NodeSummary::new(node).to_string(),
+        );
+        write!(
+            out,
+            "{} {}. {actual}",
+            if ok { "✓" } else { "✗" },
+            index + 1
+        )?;
+        match by {
+            Some(by) if !ok => writeln!(out, ", expected: {by:?}")?,
+            Some(_) => writeln!(out)?,
+            None => writeln!(out, ", expected: <nothing>")?,
+        }
+    }
+    Ok(())
+}
+
+/// Clickable nodes need to be focusable, otherwise keyboard users can't reach them.
+pub struct KeyboardFocusable;
+
+impl Rule for KeyboardFocusable {
+    fn id(&self) -> &'static str {
+        "keyboard-focusable"
+    }
+
+    fn wcag(&self) -> &'static [WcagCriterion] {
+        &[WcagCriterion::KEYBOARD]
+    }
+
+    fn check(&self, node: &AccessKitNode<'_>) -> Option<String> {
+        let data = node.data();
+        (data.supports_action(Action::Click)
+            && !data.supports_action(Action::Focus)
+            && !node.is_disabled()
+            && !is_hidden_in_tree(node))
+        .then(|| {
+            "Clickable node isn't focusable, so it can't be reached with the keyboard".to_owned()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::State;
+    use crate::filter::by;
+    use crate::test_util::{node, state};
+    use accesskit::{Node, Role};
+
+    fn button(label: &str, children: &[u64]) -> Node {
+        let mut button = node(Role::Button, Some(label), children);
+        button.add_action(Action::Focus);
+        button.add_action(Action::Click);
+        button
+    }
+
+    /// A form with focusable, hidden, disabled and non-focusable nodes, nested in groups.
+    fn form() -> State {
+        let mut hidden = node(Role::Group, Some("Advanced"), &[3]);
+        hidden.set_hidden();
+        let mut disabled = button("Delete", &[]);
+        disabled.set_disabled();
+        state([
+            (0, node(Role::Window, None, &[1, 2, 4, 5])),
+            (1, button("Name", &[])),
+            (2, hidden),
+            (3, button("Reset", &[])),
+            (4, node(Role::Group, Some("Actions"), &[6, 7, 8])),
+            (5, button("Help", &[])),
+            (6, disabled),
+            (7, node(Role::Label, Some("Status"), &[])),
+            (8, button("Save", &[])),
+        ])
+    }
+
+    #[test]
+    fn hidden_and_disabled_nodes_are_skipped() {
+        let state = form();
+        let labels: Vec<_> = tab_order(&state.root())
+            .iter()
+            .map(|node| node.label())
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                Some("Name".to_owned()),
+                Some("Save".to_owned()),
+                Some("Help".to_owned())
+            ]
+        );
+        assert_tab_order(
+            &state.root(),
+            &[by().label("Name"), by().label("Save"), by().label("Help")],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "✗ 2. button \"Save\" (#8), expected:")]
+    fn tab_order_mismatch_is_reported() {
+        assert_tab_order(
+            &form().root(),
+            &[by().label("Name"), by().label("Help"), by().label("Save")],
+        );
+    }
+
+    #[test]
+    fn clickable_nodes_need_to_be_focusable() {
+        let mut clickable = node(Role::GenericContainer, Some("Card"), &[]);
+        clickable.add_action(Action::Click);
+        let state = state([
+            (0, node(Role::Window, None, &[1, 2])),
+            (1, clickable),
+            (2, button("Open", &[])),
+        ]);
+        let ids: Vec<_> = crate::audit_node(&state.root(), &[Box::new(KeyboardFocusable)])
+            .into_iter()
+            .map(|violation| violation.node.id.0)
+            .collect();
+        assert_eq!(ids, [1]);
+    }
+}
@@ -0,0 +1,403 @@
+use crate::filter::By;
+use crate::node::accessible_name;
+use crate::snapshot::{role_name, snapshot};
+use crate::{AccessKitNode, NodeT};
+use std::fmt::Write as _;
+
+/// A partial description of a tree, see [`assert_matches_tree`].
+///
+/// Each template node is a [`By`] filter and a list of child templates.
+/// Child templates can match any descendant of the matched node, so extra nodes (like layout
+/// containers) are allowed in between.
+///
+/// ```rust ignore
+/// let template = TreeTemplate::new(by().role(Role::Window)).children([
+///     TreeTemplate::new(by().role(Role::CheckBox).label("Dark mode")),
+///     TreeTemplate::new(by().role(Role::Button).label("Save")),
+/// ]);
+/// assert_matches_tree(&harness.root(), &template);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TreeTemplate<'a> {
+    by: By<'a>,
+    children: Vec<Self>,
+    strict_order: Option<bool>,
+}
+
+impl<'a> From<By<'a>> for TreeTemplate<'a> {
+    fn from(by: By<'a>) -> Self {
+        Self::new(by)
+    }
+}
+
+impl<'a> TreeTemplate<'a> {
+    /// Create a template matching a node with the given [`By`] filter.
+    ///
+    /// Note: [`By::recursive`] is ignored, every node is checked individually.
+    pub fn new(by: By<'a>) -> Self {
+        Self {
+            by,
+            children: Vec::new(),
+            strict_order: None,
+        }
+    }
+
+    /// Add a child template, that has to match a descendant of this node.
+    pub fn child(mut self, child: impl Into<Self>) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
+    /// Add multiple child templates, see [`Self::child`].
+    pub fn children(mut self, children: impl IntoIterator<Item = impl Into<Self>>) -> Self {
+        self.children.extend(children.into_iter().map(Into::into));
+        self
+    }
+
+    /// Should the child templates match the nodes in tree order?
+    /// Default is inherited from the parent template, or false for the root.
+    pub fn strict_order(mut self, strict_order: bool) -> Self {
+        self.strict_order = Some(strict_order);
+        self
+    }
+
+    /// The number of template nodes, including this one.
+    fn len(&self) -> usize {
+        1 + self.children.iter().map(Self::len).sum::<usize>()
+    }
+}
+
+/// How a template node was matched against the tree.
+#[derive(Clone)]
+struct Alignment<'tree> {
+    node: Option<AccessKitNode<'tree>>,
+    children: Vec<Self>,
+    score: usize,
+}
+
+impl Alignment<'_> {
+    fn unmatched(template: &TreeTemplate<'_>) -> Self {
+        Self {
+            node: None,
+            children: template.children.iter().map(Self::unmatched).collect(),
+            score: 0,
+        }
+    }
+}
+
+fn descendants<'tree>(node: &AccessKitNode<'tree>, out: &mut Vec<AccessKitNode<'tree>>) {
+    for child in node.children() {
+        out.push(child);
+        descendants(&child, out);
+    }
+}
+
+/// Find the alignment of the template with the most matched template nodes, with the template
+/// root matched against `node`.
+fn align<'tree>(
+    template: &TreeTemplate<'_>,
+    node: AccessKitNode<'tree>,
+    strict_order: bool,
+) -> Alignment<'tree> {
+    if !template.by.matches(&node) {
+        return Alignment::unmatched(template);
+    }
+
+    let strict_order = template.strict_order.unwrap_or(strict_order);
+    let mut candidates = Vec::new();
+    descendants(&node, &mut candidates);
+
+    let children = align_children(&template.children, &candidates, strict_order);
+    Alignment {
+        node: Some(node),
+        score: 1 + children.iter().map(|child| child.score).sum::<usize>(),
+        children,
+    }
+}
+
+/// Assign the child templates to distinct descendants, matching as many template nodes as
+/// possible.
+fn align_children<'tree>(
+    templates: &[TreeTemplate<'_>],
+    candidates: &[AccessKitNode<'tree>],
+    strict_order: bool,
+) -> Vec<Alignment<'tree>> {
+    // alignments[t][c] is the alignment of template t with the root matched against candidate c.
+    let mut alignments: Vec<Vec<Alignment<'tree>>> = templates
+        .iter()
+        .map(|template| {
+            candidates
+                .iter()
+                .map(|candidate| align(template, *candidate, strict_order))
+                .collect()
+        })
+        .collect();
+    let scores: Vec<Vec<usize>> = alignments
+        .iter()
+        .map(|row| row.iter().map(|alignment| alignment.score).collect())
+        .collect();
+
+    let assignment = if strict_order {
+        assign_in_order(&scores, candidates.len())
+    } else {
+        assign_in_any_order(templates, &scores, candidates.len())
+    };
+
+    templates
+        .iter()
+        .zip(assignment)
+        .zip(&mut alignments)
+        .map(|((template, candidate), row)| {
+            candidate
+                .and_then(|candidate| row.get_mut(candidate))
+                .map_or_else(
+                    || Alignment::unmatched(template),
+                    |alignment| std::mem::replace(alignment, Alignment::unmatched(template)),
+                )
+        })
+        .collect()
+}
+
+/// The assignment of templates to candidates in tree order with the highest total score.
+///
+/// This is a weighted longest common subsequence, so it takes O(templates · candidates).
+#[allow(clippy::indexing_slicing)] // The indices are bounded by the table size.
+fn assign_in_order(scores: &[Vec<usize>], candidates: usize) -> Vec<Option<usize>> {
+    let templates = scores.len();
+
+    // best[t][c] is the highest total score of templates[t..] assigned to candidates[c..].
+    let mut best = vec![vec![0; candidates + 1]; templates + 1];
+    for t in (0..templates).rev() {
+        for c in (0..candidates).rev() {
+            let matched = match scores[t][c] {
+                0 => 0,
+                score => score + best[t + 1][c + 1],
+            };
+            best[t][c] = matched.max(best[t + 1][c]).max(best[t][c + 1]);
+        }
+    }
+
+    let mut assignment = vec![None; templates];
+    let (mut t, mut c) = (0, 0);
+    while t < templates && c < candidates {
+        if scores[t][c] > 0 && best[t][c] == scores[t][c] + best[t + 1][c + 1] {
+            assignment[t] = Some(c);
+            t += 1;
+            c += 1;
+        } else if best[t][c] == best[t][c + 1] {
+            c += 1;
+        } else {
+            t += 1;
+        }
+    }
+    assignment
+}
+
+/// Assign the templates to distinct candidates in any order.
+///
+/// First as many templates as possible are assigned to candidates they match completely (a
+/// maximum bipartite matching), which decides whether the tree matches. The remaining templates
+/// get the best unused partial match, to show in the report.
+#[allow(clippy::indexing_slicing)] // The indices are bounded by the table size.
+fn assign_in_any_order(
+    templates: &[TreeTemplate<'_>],
+    scores: &[Vec<usize>],
+    candidates: usize,
+) -> Vec<Option<usize>> {
+    /// Try to assign template `t`, moving previously assigned templates along augmenting paths.
+    fn augment(
+        t: usize,
+        complete: &[Vec<usize>],
+        visited: &mut [bool],
+        owners: &mut [Option<usize>],
+    ) -> bool {
+        for &c in &complete[t] {
+            if visited[c] {
+                continue;
+            }
+            visited[c] = true;
+            if owners[c].is_none_or(|owner| augment(owner, complete, visited, owners)) {
+                owners[c] = Some(t);
+                return true;
+            }
+        }
+        false
+    }
+
+    let complete: Vec<Vec<usize>> = templates
+        .iter()
+        .zip(scores)
+        .map(|(template, row)| {
+            let len = template.len();
+            (0..candidates).filter(|&c| row[c] == len).collect()
+        })
+        .collect();
+
+    let mut owners = vec![None; candidates];
+    for t in 0..templates.len() {
+        augment(t, &complete, &mut vec![false; candidates], &mut owners);
+    }
+
+    let mut assignment = vec![None; templates.len()];
+    for (c, owner) in owners.iter().enumerate() {
+        if let Some(t) = owner {
+            assignment[*t] = Some(c);
+        }
+    }
+
+    for (t, row) in scores.iter().enumerate() {
+        if assignment[t].is_some() {
+            continue;
+        }
+        let partial = (0..candidates)
+            .filter(|&c| owners[c].is_none() && row[c] > 0)
+            .max_by_key(|&c| (row[c], std::cmp::Reverse(c)));
+        if let Some(c) = partial {
+            owners[c] = Some(t);
+            assignment[t] = Some(c);
+        }
+    }
+    assignment
+}
+
+/// A short description of a node, e.g. `button "Submit"`.
+fn describe(node: &AccessKitNode<'_>) -> String {
+    match accessible_name(node) {
+        Some(name) => format!("{} {name:?}", role_name(node.role())),
+        None => role_name(node.role()),
+    }
+}
+
+fn write_alignment(
+    out: &mut String,
+    template: &TreeTemplate<'_>,
+    alignment: &Alignment<'_>,
+    depth: usize,
+) -> std::fmt::Result {
+    let indent = "  ".repeat(depth);
+    match &alignment.node {
+        Some(node) => writeln!(out, "{indent}✓ {:?} => {}", template.by, describe(node))?,
+        None => writeln!(out, "{indent}✗ {:?} => not found", template.by)?,
+    }
+    for (child, child_alignment) in template.children.iter().zip(&alignment.children) {
+        write_alignment(out, child, child_alignment, depth + 1)?;
+    }
+    Ok(())
+}
+
+/// Check whether the tree matches the template, with the template root matched against `node`.
+///
+/// See [`assert_matches_tree`].
+pub fn matches_tree<'tree, Node: NodeT<'tree> + 'tree>(
+    node: &Node,
+    template: &TreeTemplate<'_>,
+) -> bool {
+    align(template, node.accesskit_node(), false).score == template.len()
+}
+
+/// Assert that the tree matches the template, with the template root matched against `node`.
+///
+/// Only the nodes in the template are checked, other nodes in the tree are ignored.
+/// Unless [`TreeTemplate::strict_order`] is set, the child templates can match in any order.
+///
+/// # Panics
+/// - if the tree doesn't match the template. The message shows the alignment that matched the
+///   most template nodes.
+#[track_caller]
+pub fn assert_matches_tree<'tree, Node: NodeT<'tree> + 'tree>(
+    node: &Node,
+    template: &TreeTemplate<'_>,
+) {
+    let alignment = align(template, node.accesskit_node(), false);
+    if alignment.score != template.len() {
+        let mut report = String::new();
+        write_alignment(&mut report, template, &alignment, 0)
+            .expect("Writing to a String can't fail");
+        panic!(
+            "Tree doesn't match the template. Best alignment:\n{report}\nTree:\n{}",
+            snapshot(node)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{node, state};
+    use crate::{State, TreeNode, by};
+    use accesskit::Role;
+
+    /// A window with a list of rows, each with a check box.
+    fn list(rows: usize) -> State {
+        let items: Vec<u64> = (0..rows as u64).map(|row| 2 + 2 * row).collect();
+        let mut nodes = vec![
+            (0, node(Role::Window, None, &[1])),
+            (1, node(Role::List, None, &items)),
+        ];
+        for (row, id) in items.into_iter().enumerate() {
+            let label = format!("Row {row}");
+            nodes.push((id, node(Role::ListItem, Some(&label), &[id + 1])));
+            nodes.push((id + 1, node(Role::CheckBox, Some("Done"), &[])));
+        }
+        state(nodes)
+    }
+
+    fn row(label: &str) -> TreeTemplate<'_> {
+        TreeTemplate::new(by().role(Role::ListItem).label(label))
+            .child(by().role(Role::CheckBox).label("Done"))
+    }
+
+    #[test]
+    fn matching_template() {
+        let state = list(3);
+        let template = TreeTemplate::new(by().role(Role::Window)).children([
+            // The generic template must not take the node the specific one needs.
+            TreeTemplate::new(by().role(Role::ListItem)),
+            row("Row 2"),
+            row("Row 0"),
+        ]);
+        assert!(matches_tree(&TreeNode::new(state.root()), &template));
+        assert_matches_tree(&TreeNode::new(state.root()), &template);
+    }
+
+    #[test]
+    fn strict_order() {
+        let state = list(3);
+        let in_order = TreeTemplate::new(by().role(Role::Window))
+            .strict_order(true)
+            .children([row("Row 0"), row("Row 2")]);
+        let reversed = TreeTemplate::new(by().role(Role::Window))
+            .strict_order(true)
+            .children([row("Row 2"), row("Row 0")]);
+        assert!(matches_tree(&TreeNode::new(state.root()), &in_order));
+        assert!(!matches_tree(&TreeNode::new(state.root()), &reversed));
+    }
+
+    #[test]
+    #[should_panic(expected = "✗ By { label: \"Row 5\", role: ListItem } => not found")]
+    fn mismatched_template() {
+        let state = list(3);
+        let template =
+            TreeTemplate::new(by().role(Role::Window)).children([row("Row 0"), row("Row 5")]);
+        assert!(!matches_tree(&TreeNode::new(state.root()), &template));
+        assert_matches_tree(&TreeNode::new(state.root()), &template);
+    }
+
+    #[test]
+    fn large_mismatched_template() {
+        let state = list(50);
+        let labels: Vec<String> = (0..=50).map(|row| format!("Row {row}")).collect();
+        for strict_order in [false, true] {
+            let template = TreeTemplate::new(by().role(Role::Window))
+                .strict_order(strict_order)
+                .children(labels.iter().map(|label| row(label)));
+            let alignment = align(&template, state.root(), false);
+            // Everything but the missing row matches.
+            assert_eq!(
+                alignment.score,
+                template.len() - 2,
+                "strict: {strict_order}"
+            );
+        }
+    }
+}
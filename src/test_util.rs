@@ -0,0 +1,41 @@
+//! Helpers to build trees in unit tests.
+
+use crate::State;
+use accesskit::{Node, NodeId, Role, Tree, TreeId, TreeUpdate};
+
+/// A node with an optional label and the given children.
+pub(crate) fn node(role: Role, label: Option<&str>, children: &[u64]) -> Node {
+    let mut node = Node::new(role);
+    if let Some(label) = label {
+        node.set_label(label);
+    }
+    node.set_children(children.iter().copied().map(NodeId).collect::<Vec<_>>());
+    node
+}
+
+/// The first `TreeUpdate` of a tree with the given nodes. Node #0 is the root, and focused.
+pub(crate) fn tree_update(nodes: impl IntoIterator<Item = (u64, Node)>) -> TreeUpdate {
+    TreeUpdate {
+        nodes: nodes
+            .into_iter()
+            .map(|(id, node)| (NodeId(id), node))
+            .collect(),
+        tree: Some(Tree::new(NodeId(0))),
+        tree_id: TreeId::ROOT,
+        focus: NodeId(0),
+    }
+}
+
+/// A state with the given nodes, see [`tree_update`].
+pub(crate) fn state(nodes: impl IntoIterator<Item = (u64, Node)>) -> State {
+    State::new(tree_update(nodes))
+}
+
+/// The `TreeUpdate` of a window (#0) with a button for each label (#1, #2, …).
+pub(crate) fn buttons(labels: &[&str]) -> TreeUpdate {
+    let ids: Vec<u64> = (1..=labels.len() as u64).collect();
+    let buttons = (1..)
+        .zip(labels)
+        .map(|(id, label)| (id, node(Role::Button, Some(label), &[])));
+    tree_update(std::iter::once((0, node(Role::Window, None, &ids))).chain(buttons))
+}
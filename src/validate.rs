@@ -0,0 +1,380 @@
+use crate::AccessKitNode;
+use accesskit::{NodeId, TreeId, TreeUpdate};
+use accesskit_consumer::TreeState;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+/// A `TreeUpdate` that can't be applied, see [`crate::State::try_new`] and
+/// [`crate::State::try_update`].
+///
+/// Node ids are the local AccessKit node ids of the update.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TreeUpdateError {
+    /// The first update of a tree must set `TreeUpdate::tree`.
+    MissingTree,
+
+    /// The first update of a tree must be for [`TreeId::ROOT`].
+    NotRootTree { tree_id: TreeId },
+
+    /// The root node is neither in the update nor in the current tree.
+    MissingRoot { root: NodeId },
+
+    /// A node has a child that is neither in the update nor in the current tree.
+    DanglingChild { parent: NodeId, child: NodeId },
+
+    /// A node is listed as a child more than once in the update, or has more than one parent in
+    /// the tree after the update.
+    DuplicateChild { child: NodeId },
+
+    /// A node is in the update more than once.
+    DuplicateNode { node: NodeId },
+
+    /// A node is neither the root, the child of a node in the update, nor in the current tree.
+    UnreachableNode { node: NodeId },
+
+    /// The focused node isn't in the tree after the update, e.g. because the update removes it.
+    MissingFocus { focus: NodeId },
+}
+
+impl Display for TreeUpdateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingTree => write!(
+                f,
+                "The first TreeUpdate must set `tree` (the tree's root and toolkit info)"
+            ),
+            Self::NotRootTree { tree_id } => write!(
+                f,
+                "The first TreeUpdate must be for TreeId::ROOT, but it is for {tree_id:?}"
+            ),
+            Self::MissingRoot { root } => write!(
+                f,
+                "The root node #{} is neither in the TreeUpdate nor in the current tree",
+                root.0
+            ),
+            Self::DanglingChild { parent, child } => write!(
+                f,
+                "Node #{} has the child #{}, which is neither in the TreeUpdate nor in the current tree",
+                parent.0, child.0
+            ),
+            Self::DuplicateChild { child } => write!(
+                f,
+                "Node #{} is listed as a child more than once, or has more than one parent after the TreeUpdate",
+                child.0
+            ),
+            Self::DuplicateNode { node } => {
+                write!(f, "Node #{} is in the TreeUpdate more than once", node.0)
+            }
+            Self::UnreachableNode { node } => write!(
+                f,
+                "Node #{} is neither the root, a child of another node in the TreeUpdate, nor in the current tree",
+                node.0
+            ),
+            Self::MissingFocus { focus } => write!(
+                f,
+                "The focused node #{} isn't in the tree after the TreeUpdate",
+                focus.0
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TreeUpdateError {}
+
+/// The `accesskit_consumer` ids of the nodes of the root tree, by their local id.
+///
+/// This allows looking up the nodes a `TreeUpdate` touches in the [`TreeState`], without walking
+/// the whole tree for every update.
+#[derive(Debug, Default)]
+pub(crate) struct NodeIndex(HashMap<NodeId, accesskit_consumer::NodeId>);
+
+impl NodeIndex {
+    /// Index all nodes of the tree.
+    pub(crate) fn new(tree: &TreeState) -> Self {
+        fn insert_subtree(index: &mut NodeIndex, node: &AccessKitNode<'_>) {
+            index.insert(node);
+            for child in node.children() {
+                insert_subtree(index, &child);
+            }
+        }
+
+        let mut index = Self::default();
+        insert_subtree(&mut index, &tree.root());
+        index
+    }
+
+    /// Add a node, e.g. after an update added it.
+    pub(crate) fn insert(&mut self, node: &AccessKitNode<'_>) {
+        let (id, tree_id) = node.locate();
+        if tree_id == TreeId::ROOT {
+            self.0.insert(id, node.id());
+        }
+    }
+
+    /// Remove a node, e.g. after an update removed it.
+    pub(crate) fn remove(&mut self, node: &AccessKitNode<'_>) {
+        let (id, tree_id) = node.locate();
+        if tree_id == TreeId::ROOT {
+            self.0.remove(&id);
+        }
+    }
+
+    fn get<'tree>(&self, tree: &'tree TreeState, id: NodeId) -> Option<AccessKitNode<'tree>> {
+        self.0.get(&id).and_then(|id| tree.node_by_id(*id))
+    }
+}
+
+/// The root tree as it will be after an update, looking up the nodes the update doesn't change
+/// in the current tree.
+struct TreeAfter<'a> {
+    /// The children of the nodes in the update.
+    children: HashMap<NodeId, &'a [NodeId]>,
+    /// The parents of the children of the nodes in the update.
+    parents: HashMap<NodeId, NodeId>,
+    current: Option<(&'a TreeState, &'a NodeIndex)>,
+    root: Option<NodeId>,
+}
+
+impl TreeAfter<'_> {
+    fn current_node(&self, id: NodeId) -> Option<AccessKitNode<'_>> {
+        self.current.and_then(|(tree, index)| index.get(tree, id))
+    }
+
+    fn current_parent(&self, id: NodeId) -> Option<NodeId> {
+        Some(self.current_node(id)?.parent()?.locate().0)
+    }
+
+    fn exists(&self, id: NodeId) -> bool {
+        self.children.contains_key(&id) || self.current_node(id).is_some()
+    }
+
+    fn parent(&self, id: NodeId) -> Option<NodeId> {
+        if let Some(parent) = self.parents.get(&id) {
+            return Some(*parent);
+        }
+        // A node keeps its current parent, unless the update replaces that parent's children.
+        self.current_parent(id)
+            .filter(|parent| !self.children.contains_key(parent))
+    }
+
+    /// Returns true if the node is the root or a descendant of it.
+    fn is_attached(&self, id: NodeId) -> bool {
+        let mut visited = HashSet::new();
+        let mut current = id;
+        while Some(current) != self.root {
+            // A cycle of nodes that was detached from the root.
+            if !visited.insert(current) {
+                return false;
+            }
+            match self.parent(current) {
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Check that `update` can be applied to `tree`, or used to create a new tree if `tree` is
+/// `None`, without panicking in `accesskit_consumer`.
+///
+/// Updates for subtrees (other than [`TreeId::ROOT`]) aren't validated.
+pub(crate) fn validate_update(
+    update: &TreeUpdate,
+    tree: Option<(&TreeState, &NodeIndex)>,
+) -> Result<(), TreeUpdateError> {
+    if tree.is_none() {
+        if update.tree.is_none() {
+            return Err(TreeUpdateError::MissingTree);
+        }
+        if update.tree_id != TreeId::ROOT {
+            return Err(TreeUpdateError::NotRootTree {
+                tree_id: update.tree_id,
+            });
+        }
+    }
+    if update.tree_id != TreeId::ROOT {
+        return Ok(());
+    }
+
+    let mut children = HashMap::new();
+    for (id, node) in &update.nodes {
+        if children.insert(*id, node.children()).is_some() {
+            return Err(TreeUpdateError::DuplicateNode { node: *id });
+        }
+    }
+
+    let mut after = TreeAfter {
+        children,
+        parents: HashMap::new(),
+        current: tree,
+        root: update
+            .tree
+            .as_ref()
+            .map(|tree| tree.root)
+            .or_else(|| tree.map(|(tree, _)| tree.root().locate().0)),
+    };
+
+    if let Some(root) = after.root
+        && !after.exists(root)
+    {
+        return Err(TreeUpdateError::MissingRoot { root });
+    }
+
+    for (parent, node) in &update.nodes {
+        for child in node.children() {
+            if after.parents.insert(*child, *parent).is_some() || Some(*child) == after.root {
+                return Err(TreeUpdateError::DuplicateChild { child: *child });
+            }
+            if !after.exists(*child) {
+                return Err(TreeUpdateError::DanglingChild {
+                    parent: *parent,
+                    child: *child,
+                });
+            }
+        }
+    }
+
+    for (id, _) in &update.nodes {
+        if Some(*id) != after.root
+            && !after.parents.contains_key(id)
+            && after.current_node(*id).is_none()
+        {
+            return Err(TreeUpdateError::UnreachableNode { node: *id });
+        }
+    }
+
+    // A node that moves to a new parent must be removed from its current parent, unless the
+    // update detaches that parent.
+    for (parent, node) in &update.nodes {
+        for child in node.children() {
+            if let Some(current_parent) = after.current_parent(*child)
+                && current_parent != *parent
+                && !after.children.contains_key(&current_parent)
+                && after.is_attached(current_parent)
+            {
+                return Err(TreeUpdateError::DuplicateChild { child: *child });
+            }
+        }
+    }
+
+    if !after.exists(update.focus) || !after.is_attached(update.focus) {
+        return Err(TreeUpdateError::MissingFocus {
+            focus: update.focus,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::node;
+    use accesskit::{Role, Tree};
+
+    fn update(nodes: &[(u64, &[u64])], focus: u64) -> TreeUpdate {
+        TreeUpdate {
+            nodes: nodes
+                .iter()
+                .map(|(id, children)| (NodeId(*id), node(Role::GenericContainer, None, children)))
+                .collect(),
+            tree: None,
+            tree_id: TreeId::ROOT,
+            focus: NodeId(focus),
+        }
+    }
+
+    /// Validate an update to the tree 0 -> [1, 2], 1 -> [3], 2 -> [4].
+    fn validate(update: &TreeUpdate) -> Result<(), TreeUpdateError> {
+        let mut initial =
+            self::update(&[(0, &[1, 2]), (1, &[3]), (2, &[4]), (3, &[]), (4, &[])], 4);
+        initial.tree = Some(Tree::new(NodeId(0)));
+        assert_eq!(validate_update(&initial, None), Ok(()));
+        let tree = accesskit_consumer::Tree::new(initial, true);
+        let index = NodeIndex::new(tree.state());
+        validate_update(update, Some((tree.state(), &index)))
+    }
+
+    #[test]
+    fn valid_update() {
+        let added = update(&[(1, &[3, 5]), (5, &[])], 5);
+        assert_eq!(validate(&added), Ok(()));
+        let moved = update(&[(1, &[]), (2, &[4, 3])], 3);
+        assert_eq!(validate(&moved), Ok(()));
+    }
+
+    #[test]
+    fn missing_child() {
+        let update = update(&[(1, &[3, 5])], 0);
+        assert_eq!(
+            validate(&update),
+            Err(TreeUpdateError::DanglingChild {
+                parent: NodeId(1),
+                child: NodeId(5)
+            })
+        );
+    }
+
+    #[test]
+    fn missing_focus() {
+        let update = update(&[], 5);
+        assert_eq!(
+            validate(&update),
+            Err(TreeUpdateError::MissingFocus { focus: NodeId(5) })
+        );
+    }
+
+    #[test]
+    fn focus_on_removed_node() {
+        // Removes the subtree of node 2, but keeps the focus on node 4.
+        let update = update(&[(0, &[1])], 4);
+        assert_eq!(
+            validate(&update),
+            Err(TreeUpdateError::MissingFocus { focus: NodeId(4) })
+        );
+    }
+
+    #[test]
+    fn duplicate_parent() {
+        // Node 3 is still a child of node 1.
+        let update = update(&[(2, &[4, 3])], 0);
+        assert_eq!(
+            validate(&update),
+            Err(TreeUpdateError::DuplicateChild { child: NodeId(3) })
+        );
+    }
+
+    #[test]
+    fn move_out_of_removed_parent() {
+        // Node 1 is removed, so node 3 can move to node 2 without updating node 1.
+        let update = update(&[(0, &[2]), (2, &[4, 3])], 3);
+        assert_eq!(validate(&update), Ok(()));
+    }
+
+    #[test]
+    fn duplicate_node() {
+        let update = update(&[(1, &[5]), (1, &[3, 5]), (5, &[])], 5);
+        assert_eq!(
+            validate(&update),
+            Err(TreeUpdateError::DuplicateNode { node: NodeId(1) })
+        );
+    }
+
+    #[test]
+    fn index_follows_updates() {
+        let mut initial = update(&[(0, &[1]), (1, &[])], 0);
+        initial.tree = Some(Tree::new(NodeId(0)));
+        let mut state = crate::State::new(initial);
+        state.update(update(&[(1, &[2]), (2, &[])], 2));
+        assert_eq!(
+            state.try_update(update(&[(0, &[1, 2])], 2)),
+            Err(TreeUpdateError::DuplicateChild { child: NodeId(2) })
+        );
+        state.update(update(&[(0, &[2])], 2));
+        assert_eq!(
+            state.try_update(update(&[], 1)),
+            Err(TreeUpdateError::MissingFocus { focus: NodeId(1) })
+        );
+    }
+}